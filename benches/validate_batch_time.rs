@@ -0,0 +1,36 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use crypto_bigint::Encoding;
+use csidh::{CsidhParams, PrivateKey, PublicKey, Uint};
+use rand::{SeedableRng, rngs::StdRng};
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Validate batch time");
+    group.sample_size(10);
+
+    const NUMBER_OF_KEYS: usize = 16;
+
+    let params = CsidhParams::CSIDH_512;
+    let mut rng = StdRng::from_rng(rand::thread_rng()).unwrap();
+    let keys: [_; NUMBER_OF_KEYS] = core::array::from_fn(|_| {
+        let private_key = PrivateKey::random(params, &mut rng);
+        Uint::from_le_bytes(PublicKey::from(&private_key, &mut rng).to_bytes())
+    });
+
+    for n in [1, 4, NUMBER_OF_KEYS] {
+        let keys = &keys[..n];
+        group.bench_with_input(BenchmarkId::new("try_new", n), keys, |b, keys| {
+            b.iter(|| {
+                keys.iter()
+                    .map(|&key| PublicKey::try_new(params, key, &mut rng))
+                    .for_each(drop)
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("validate_batch", n), keys, |b, keys| {
+            b.iter(|| PublicKey::validate_batch(params, keys, &mut rng).for_each(drop))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,112 @@
+//! Searches for CSIDH primes p = 4 ∏ ℓ<sub>i</sub> - 1, and prints the source of a `csidh_params`
+//! submodule for each result.
+//!
+//! ```text
+//! csidh-prime-search <BITS> [--drop L,L,...] [--add L,L,...] [--name NAME] [--count COUNT]
+//!     [--macro]
+//! ```
+//!
+//! `--drop` removes primes from the smallest odd primes, `--add` includes other primes in every
+//! result, and together they replace primes. `--name` names the parameters, `BITS` by default,
+//! and `--count` sets the number of results, 1 by default. `--macro` prints an invocation of the
+//! `csidh_params!` macro instead of a submodule.
+//!
+//! CSIDH-1024 is found with `1024 --add 863`, and CSIDH-1792 with
+//! `1792 --drop 3,5,7,11,13,17,19,23,29,31,1297`.
+
+use std::{env, process::ExitCode};
+
+use csidh::PrimeSearch;
+
+const USAGE: &str = "Usage: csidh-prime-search <BITS> [--drop L,L,...] [--add L,L,...] \
+    [--name NAME] [--count COUNT] [--macro]";
+
+struct Args {
+    bits: u32,
+    dropped: Vec<u64>,
+    added: Vec<u64>,
+    name: String,
+    count: usize,
+    macro_source: bool,
+}
+
+fn parse_primes(value: &str) -> Result<Vec<u64>, String> {
+    (value.split(','))
+        .map(|li| {
+            li.trim()
+                .parse()
+                .map_err(|_| format!("Invalid prime: {li}"))
+        })
+        .collect()
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let bits = args.next().ok_or("Missing number of bits")?;
+    let bits = bits
+        .parse()
+        .map_err(|_| format!("Invalid number of bits: {bits}"))?;
+    let mut parsed = Args {
+        bits,
+        dropped: Vec::new(),
+        added: Vec::new(),
+        name: bits.to_string(),
+        count: 1,
+        macro_source: false,
+    };
+    while let Some(option) = args.next() {
+        if option == "--macro" {
+            parsed.macro_source = true;
+            continue;
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value of {option}"))?;
+        match option.as_str() {
+            "--drop" => parsed.dropped = parse_primes(&value)?,
+            "--add" => parsed.added = parse_primes(&value)?,
+            "--name" => parsed.name = value,
+            "--count" => {
+                parsed.count = value
+                    .parse()
+                    .map_err(|_| format!("Invalid count: {value}"))?;
+            }
+            _ => return Err(format!("Unknown option: {option}")),
+        }
+    }
+    Ok(parsed)
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{error}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let search = PrimeSearch::new(args.bits)
+        .with_dropped_primes(args.dropped)
+        .with_added_primes(args.added);
+    let mut found = 0;
+    for params in search.results().take(args.count) {
+        found += 1;
+        let lis = params.lis();
+        eprintln!(
+            "Found a {}-bit prime p with {} primes, the largest being {}",
+            params.p().bits_vartime(),
+            lis.len(),
+            lis[lis.len() - 1]
+        );
+        if args.macro_source {
+            print!("{}", params.to_macro_source(&args.name));
+        } else {
+            print!("{}", params.to_module_source(&args.name));
+        }
+    }
+    if found == 0 {
+        eprintln!("No prime p of at most {} bits was found", args.bits);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
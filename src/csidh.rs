@@ -1,129 +1,438 @@
 use crypto_bigint::{
-    Odd, PrecomputeInverter, Random, Uint,
-    modular::{ConstMontyForm, ConstMontyParams, SafeGcdInverter},
+    U64, WrappingMul,
     rand_core::CryptoRngCore,
+    subtle::{Choice, ConditionallySelectable, ConstantTimeEq, ConstantTimeGreater},
 };
+use zeroize::Zeroizing;
 
+#[cfg(target_pointer_width = "64")]
+use crate::field::Fp512;
 use crate::{
-    csidh_params::CsidhParams, montgomery_curve::MontgomeryCurve, montgomery_point::MontgomeryPoint,
+    csidh_params::{EvaluationMode, Params},
+    field::Field,
+    isogeny::Isogeny,
+    montgomery_curve::MontgomeryCurve,
+    montgomery_point::MontgomeryPoint,
+    strategy,
 };
 
-pub fn csidh<
-    const SAT_LIMBS: usize,
-    const N: usize,
-    MOD: ConstMontyParams<SAT_LIMBS>,
-    const UNSAT_LIMBS: usize,
->(
-    params: CsidhParams<SAT_LIMBS, N, MOD>,
-    mut path: [u32; N],
-    start: ConstMontyForm<MOD, SAT_LIMBS>,
+pub fn csidh<P: Params>(
+    params: P,
+    key: &[i32],
+    start: P::Element,
     rng: &mut impl CryptoRngCore,
-) -> ConstMontyForm<MOD, SAT_LIMBS>
-where
-    Odd<Uint<SAT_LIMBS>>: PrecomputeInverter<
-            Inverter = SafeGcdInverter<SAT_LIMBS, UNSAT_LIMBS>,
-            Output = Uint<SAT_LIMBS>,
-        >,
-{
+) -> P::Element {
+    #[cfg(target_pointer_width = "64")]
+    if Fp512::handles(params.p().as_ref()) {
+        return evaluate(params, key, Fp512::from_field(&start), rng).to_field(&start);
+    }
+    evaluate(params, key, start, rng)
+}
+
+/// Evaluates the action in the evaluation mode of `params`, with the arithmetic of `F`.
+fn evaluate<P: Params, F: Field>(
+    params: P,
+    key: &[i32],
+    start: F,
+    rng: &mut impl CryptoRngCore,
+) -> F {
+    match params.evaluation_mode() {
+        EvaluationMode::VariableTime => variable_time(params, key, start, rng),
+        EvaluationMode::ConstantTime => constant_time(params, key, start, rng),
+        EvaluationMode::DummyFree => dummy_free(params, key, start, rng),
+    }
+}
+
+fn variable_time<P: Params, F: Field>(
+    params: P,
+    key: &[i32],
+    start: F,
+    rng: &mut impl CryptoRngCore,
+) -> F {
     let lis = params.lis();
     let mut curve = MontgomeryCurve::new(params, start);
 
-    let mut dummies: [u32; N] = {
-        let mut temp = [0; N];
-        for i in 0..N {
-            temp[i] = 10 - path[i];
+    // Working copies of the secret key, wiped when dropped.
+    let mut path = Zeroizing::new(params.secret_array());
+    let path: &mut [i32] = path.as_mut();
+    path.copy_from_slice(key);
+    let mut dummies = Zeroizing::new(params.secret_array());
+    let dummies: &mut [u32] = dummies.as_mut();
+    for i in 0..lis.len() {
+        dummies[i] = params.bounds()[i].saturating_sub(path[i].unsigned_abs());
+    }
+
+    let mut k = params.integer(4);
+    for (i, li) in lis.iter().enumerate() {
+        if path[i] == 0 && dummies[i] == 0 {
+            k = k.wrapping_mul(&params.integer(*li));
         }
-        temp
-    };
+    }
 
-    let mut k = Uint::from(4u32);
+    while !path.iter().all(|&x| x == 0) || !dummies.iter().all(|&x| x == 0) {
+        // A point on the curve walks in the positive direction, a point on the twist in the
+        // negative one. Each round picks one of them at random.
+        let (point, twist_point) = curve.random_elligator_points(rng);
+        let (point_p, sign) = if rng.next_u32() & 1 == 0 {
+            (point, 1)
+        } else {
+            (twist_point, -1)
+        };
 
-    while !path.into_iter().all(|x| x == 0) || !dummies.into_iter().all(|x| x == 0) {
-        let x = ConstMontyForm::new(&Uint::random(rng));
+        let mut primes = params.array(0usize);
+        let primes = primes.as_mut();
+        let mut len = 0;
+        // Primes not handled in this round must be removed from the order of the point too.
+        let mut cofactor = k.clone();
+        for (i, li) in lis.iter().enumerate() {
+            if path[i] * sign > 0 || dummies[i] > 0 {
+                primes[len] = i;
+                len += 1;
+            } else if path[i] != 0 {
+                cofactor = cofactor.wrapping_mul(&params.integer(*li));
+            }
+        }
+        let point_p = point_p * &cofactor;
 
-        if let Some(mut point_p) = curve.lift(x) {
-            point_p = point_p * k;
+        curve = strategy::walk(
+            curve,
+            &primes[..len],
+            [point_p],
+            |curve, i, [point_k], pending| {
+                if point_k.is_infinity() {
+                    return curve;
+                }
+                let li = lis[i];
+                let isogeny = Isogeny::new(curve.clone(), point_k, li);
+                let codomain = isogeny.codomain();
 
-            let path_copy = path;
-            let dummies_copy: [u32; N] = dummies;
+                let curve = if path[i] * sign > 0 {
+                    pending.update(|[point]| [isogeny.image(&codomain, &point)]);
+                    path[i] -= sign;
+                    codomain
+                } else {
+                    pending.update(|[point]| [point.mul_vartime(&U64::from_u64(li))]);
+                    dummies[i] -= 1;
+                    curve
+                };
 
-            let s = lis
-                .iter()
-                .enumerate()
-                .filter(|(i, _)| path_copy[*i] > 0 || dummies_copy[*i] > 0);
+                if path[i] == 0 && dummies[i] == 0 {
+                    k = k.wrapping_mul(&params.integer(li));
+                }
+                curve
+            },
+        );
+    }
+    curve.a2()
+}
 
-            for (i, li) in s.clone() {
-                let m = {
-                    let mut temp = Uint::ONE;
-                    for (_, li) in s.clone().filter(|(j, _)| *j > i) {
-                        temp *= Uint::<SAT_LIMBS>::from(*li);
-                    }
-                    temp
-                };
+/// Evaluates the action with a schedule that only depends on the public bound of the exponents.
+///
+/// Every prime goes through exactly as many steps as its bound, each computing both an isogeny and
+/// a multiplication of the point by the prime. Each round samples a point on the curve and a point
+/// on its twist, and the kernel is taken from the one matching the sign of the exponent with a
+/// constant-time swap. The result of the isogeny is kept for the first `|key[i]|` steps only, and
+/// the selection is done in constant time too. What remains observable is whether the multiples
+/// of the random points had the expected order, which depends on the randomness but not on the
+/// key.
+fn constant_time<P: Params, F: Field>(
+    params: P,
+    key: &[i32],
+    start: F,
+    rng: &mut impl CryptoRngCore,
+) -> F {
+    let lis = params.lis();
+    let mut curve = MontgomeryCurve::new(params, start);
+
+    // Working copy of the secret key, wiped when dropped.
+    let mut path = Zeroizing::new(params.secret_array());
+    let path: &mut [i32] = path.as_mut();
+    path.copy_from_slice(key);
+    // Steps left for each prime, real or dummy. Unlike `path`, it is public.
+    let mut remaining = params.array(0u32);
+    let remaining = remaining.as_mut();
+    remaining.copy_from_slice(params.bounds());
+
+    let mut k = params.integer(4);
+    for (i, li) in lis.iter().enumerate() {
+        if remaining[i] == 0 {
+            k = k.wrapping_mul(&params.integer(*li));
+        }
+    }
 
-                let point_k = point_p * m;
+    while remaining.iter().any(|&x| x > 0) {
+        let (point_p, point_q) = curve.random_elligator_points(rng);
+        let point_p = point_p * &k;
+        let point_q = point_q * &k;
 
+        let mut primes = params.array(0usize);
+        let primes = primes.as_mut();
+        let mut len = 0;
+        for (i, steps) in remaining.iter().enumerate() {
+            if *steps > 0 {
+                primes[len] = i;
+                len += 1;
+            }
+        }
+
+        curve = strategy::walk(
+            curve,
+            &primes[..len],
+            [point_p, point_q],
+            |curve, i, [mut point_k, mut point_other], pending| {
+                let li = U64::from_u64(lis[i]);
+
+                // After the swap, `point_k` is on the side of the direction to walk in. Whether or
+                // not an isogeny is computed, the factor li must be removed from the order of the
+                // points on the other side.
+                let negative = is_negative(path[i]);
+                MontgomeryPoint::conditional_swap(&mut point_k, &mut point_other, negative);
+                pending.update(|[mut point_p, mut point_q]| {
+                    MontgomeryPoint::conditional_swap(&mut point_p, &mut point_q, negative);
+                    [point_p, point_q.mul_vartime(&li)]
+                });
+
+                let mut curve = curve;
                 if !point_k.is_infinity() {
-                    let mut tau = ConstMontyForm::ONE;
-                    let mut sigma = ConstMontyForm::ZERO;
+                    let isogeny = Isogeny::new(curve.clone(), point_k, lis[i]);
+                    let codomain = isogeny.codomain();
 
-                    for multiple in point_k.multiples(Uint::from(*li - 1)) {
-                        let x = multiple.x();
-                        tau *= x;
-                        sigma = sigma + x - x.inv().unwrap();
+                    let real = !path[i].ct_eq(&0);
+                    curve = MontgomeryCurve::new_projective(
+                        params,
+                        F::ct_select(curve.a24_plus(), codomain.a24_plus(), real),
+                        F::ct_select(curve.c24(), codomain.c24(), real),
+                    );
+                    pending.update(|[point_p, point_q]| {
+                        let image_p = isogeny.image(&codomain, &point_p);
+                        let image_q = isogeny.image(&codomain, &point_q);
+                        let multiple = point_p.mul_vartime(&li);
+                        [
+                            MontgomeryPoint::new(
+                                curve.clone(),
+                                F::ct_select(multiple.X(), image_p.X(), real),
+                                F::ct_select(multiple.Z(), image_p.Z(), real),
+                            ),
+                            MontgomeryPoint::new(
+                                curve.clone(),
+                                F::ct_select(point_q.X(), image_q.X(), real),
+                                F::ct_select(point_q.Z(), image_q.Z(), real),
+                            ),
+                        ]
+                    });
+                    let step = i32::conditional_select(&1, &-1, negative);
+                    path[i] -= i32::conditional_select(&0, &step, real);
+
+                    remaining[i] -= 1;
+                    if remaining[i] == 0 {
+                        k = k.wrapping_mul(&params.integer(lis[i]));
                     }
+                }
 
-                    let three = ConstMontyForm::new(&Uint::from(3u32));
+                pending.update(|[mut point_p, mut point_q]| {
+                    MontgomeryPoint::conditional_swap(&mut point_p, &mut point_q, negative);
+                    [point_p, point_q]
+                });
+                curve
+            },
+        );
+    }
+    curve.a2()
+}
 
-                    if path[i] > 0 {
-                        let b = tau * (curve.a2() - sigma * three);
+/// Evaluates the action without dummy isogenies.
+///
+/// Every prime goes through exactly as many real isogenies as its key bound b, twice its bound:
+/// `(b + key[i]) / 2` computed with a kernel on the curve, and `(b - key[i]) / 2` with a kernel on
+/// its twist, which walk in the opposite direction. Each round samples a point on both, and the
+/// kernel is taken from one or the other with a constant-time swap. The key elements must be
+/// even.
+fn dummy_free<P: Params, F: Field>(
+    params: P,
+    key: &[i32],
+    start: F,
+    rng: &mut impl CryptoRngCore,
+) -> F {
+    let lis = params.lis();
+    let mut curve = MontgomeryCurve::new(params, start);
 
-                        curve = MontgomeryCurve::new(params, b);
-                        point_p = {
-                            let x = point_p.X();
-                            let z = point_p.Z();
-                            let x_plus_z = x + z;
-                            let x_minus_z = x - z;
+    // Isogenies left for each prime, in both directions. Unlike `positive`, it is public.
+    let mut remaining = params.array(0u32);
+    let remaining = remaining.as_mut();
+    for (steps, bound) in remaining.iter_mut().zip(params.bounds()) {
+        *steps = 2 * bound;
+    }
+    // Isogenies left to compute in the positive direction, wiped when dropped.
+    let mut positive = Zeroizing::new(params.secret_array());
+    let positive: &mut [u32] = positive.as_mut();
+    for i in 0..lis.len() {
+        positive[i] = remaining[i].wrapping_add_signed(key[i]) / 2;
+    }
 
-                            let mut temp_x = ConstMontyForm::ONE;
-                            let mut temp_z = ConstMontyForm::ONE;
-                            for multiple in point_k.multiples(Uint::from(li / 2)) {
-                                let xi = multiple.X();
-                                let zi = multiple.Z();
+    let mut k = params.integer(4);
+    for (i, li) in lis.iter().enumerate() {
+        if remaining[i] == 0 {
+            k = k.wrapping_mul(&params.integer(*li));
+        }
+    }
 
-                                let a = x_minus_z * (xi + zi);
-                                let b = x_plus_z * (xi - zi);
+    while remaining.iter().any(|&x| x > 0) {
+        let (point_p, point_q) = curve.random_elligator_points(rng);
+        let point_p = point_p * &k;
+        let point_q = point_q * &k;
 
-                                temp_x *= a + b;
-                                temp_z *= a - b;
-                            }
+        let mut primes = params.array(0usize);
+        let primes = primes.as_mut();
+        let mut len = 0;
+        for (i, steps) in remaining.iter().enumerate() {
+            if *steps > 0 {
+                primes[len] = i;
+                len += 1;
+            }
+        }
 
-                            let x_prime = x * temp_x.square();
-                            let z_prime = z * temp_z.square();
+        curve = strategy::walk(
+            curve,
+            &primes[..len],
+            [point_p, point_q],
+            |curve, i, [mut point_k, mut point_other], pending| {
+                let li = U64::from_u64(lis[i]);
 
-                            MontgomeryPoint::new(curve, x_prime, z_prime)
-                        };
-                        path[i] -= 1;
-                    } else {
-                        let _ = tau * (curve.a2() - sigma * three);
+                // After the swap, `point_k` is on the side of the direction to walk in. Whether or
+                // not an isogeny is computed, the factor li must be removed from the order of the
+                // points on the other side.
+                let negative = !positive[i].ct_gt(&0);
+                MontgomeryPoint::conditional_swap(&mut point_k, &mut point_other, negative);
+                pending.update(|[mut point_p, mut point_q]| {
+                    MontgomeryPoint::conditional_swap(&mut point_p, &mut point_q, negative);
+                    [point_p, point_q.mul_vartime(&li)]
+                });
 
-                        point_p = point_p * Uint::from(*li);
-                        dummies[i] -= 1;
-                    }
+                let mut curve = curve;
+                if !point_k.is_infinity() {
+                    let isogeny = Isogeny::new(curve, point_k, lis[i]);
+                    curve = isogeny.codomain();
+                    pending.update(|[point_p, point_q]| {
+                        [
+                            isogeny.image(&curve, &point_p),
+                            isogeny.image(&curve, &point_q),
+                        ]
+                    });
+                    positive[i] -= u32::conditional_select(&1, &0, negative);
 
-                    if path[i] == 0 && dummies[i] == 0 {
-                        k *= Uint::<SAT_LIMBS>::from(*li);
+                    remaining[i] -= 1;
+                    if remaining[i] == 0 {
+                        k = k.wrapping_mul(&params.integer(lis[i]));
                     }
                 }
-            }
-        }
+
+                pending.update(|[mut point_p, mut point_q]| {
+                    MontgomeryPoint::conditional_swap(&mut point_p, &mut point_q, negative);
+                    [point_p, point_q]
+                });
+                curve
+            },
+        );
     }
     curve.a2()
 }
 
+/// Returns whether `x` is negative, in constant time.
+fn is_negative(x: i32) -> Choice {
+    Choice::from((x as u32 >> 31) as u8)
+}
+
 #[cfg(test)]
 mod tests {
+    use crypto_bigint::{Uint, modular::ConstMontyForm};
+
     use super::*;
+    use crate::{
+        CsidhParams, PrivateKey,
+        csidh_params::{csidh_512, toy},
+    };
+
+    #[test]
+    fn constant_time_matches_variable_time() {
+        let mut rng = rand::thread_rng();
+        let params = toy::TOY;
+        let constant_time = params.with_evaluation_mode(EvaluationMode::ConstantTime);
+        for _ in 0..4 {
+            let key = PrivateKey::random(params, &mut rng);
+            let start = csidh(params, key.key(), ConstMontyForm::ZERO, &mut rng);
+            assert_eq!(
+                csidh(constant_time, key.key(), start, &mut rng),
+                csidh(params, key.key(), start, &mut rng)
+            );
+        }
+    }
+
+    #[test]
+    fn dummy_free_matches_variable_time() {
+        let mut rng = rand::thread_rng();
+        let params = toy::TOY;
+        let dummy_free = params.with_evaluation_mode(EvaluationMode::DummyFree);
+        for _ in 0..4 {
+            let key = PrivateKey::random(dummy_free, &mut rng);
+            let start = csidh(params, key.key(), ConstMontyForm::ZERO, &mut rng);
+            assert_eq!(
+                csidh(dummy_free, key.key(), start, &mut rng),
+                csidh(params, key.key(), start, &mut rng)
+            );
+        }
+    }
+
+    #[test]
+    fn modes_match_with_custom_bounds() {
+        let mut rng = rand::thread_rng();
+        let bounds = [9, 0, 4, 3, 1, 2, 1, 1, 0, 1, 2];
+        let params = toy::TOY.with_bounds(bounds);
+        let constant_time = params.with_evaluation_mode(EvaluationMode::ConstantTime);
+        // Halved bounds, so that dummy-free keys are within the bounds of the other modes.
+        let dummy_free = toy::TOY
+            .with_bounds(bounds.map(|bound| bound / 2))
+            .with_evaluation_mode(EvaluationMode::DummyFree);
+        for _ in 0..2 {
+            let key = PrivateKey::random(dummy_free, &mut rng);
+            let expected = csidh(params, key.key(), ConstMontyForm::ZERO, &mut rng);
+            assert_eq!(
+                csidh(constant_time, key.key(), ConstMontyForm::ZERO, &mut rng),
+                expected
+            );
+            assert_eq!(
+                csidh(dummy_free, key.key(), ConstMontyForm::ZERO, &mut rng),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn negative_exponents_invert_the_action() {
+        let mut rng = rand::thread_rng();
+        let params = toy::TOY;
+        for _ in 0..4 {
+            let key = PrivateKey::random(params, &mut rng);
+            let inverse = key.key().map(|e| -e);
+            let public_key = csidh(params, key.key(), ConstMontyForm::ZERO, &mut rng);
+            assert_eq!(
+                csidh(params, &inverse, public_key, &mut rng),
+                ConstMontyForm::ZERO
+            );
+        }
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn fp512_matches_const_monty_form() {
+        let mut rng = rand::thread_rng();
+        let params = CsidhParams::CSIDH_512.with_evaluation_mode(EvaluationMode::ConstantTime);
+        let key = PrivateKey::random(params, &mut rng);
+        let start = ConstMontyForm::<csidh_512::MOD, { csidh_512::LIMBS }>::ZERO;
+        assert_eq!(
+            evaluate(params, key.key(), Fp512::from_field(&start), &mut rng).to_field(&start),
+            evaluate(params, key.key(), start, &mut rng)
+        );
+    }
 
     #[test]
     fn csidh_512_1() {
@@ -134,7 +443,7 @@ mod tests {
             temp
         };
         let start = ConstMontyForm::ZERO;
-        let public_key = csidh(params, path, start, &mut rand::thread_rng());
+        let public_key = csidh(params, &path, start, &mut rand::thread_rng());
         assert_eq!(
             public_key,
             ConstMontyForm::new(&Uint::from_be_hex(
@@ -154,7 +463,7 @@ mod tests {
             temp
         };
         let start = ConstMontyForm::ZERO;
-        let public_key = csidh(params, path, start, &mut rand::thread_rng());
+        let public_key = csidh(params, &path, start, &mut rand::thread_rng());
         assert_eq!(
             public_key,
             ConstMontyForm::new(&Uint::from_be_hex(
@@ -175,7 +484,7 @@ mod tests {
             temp
         };
         let start = ConstMontyForm::ZERO;
-        let public_key = csidh(params, path, start, &mut rand::thread_rng());
+        let public_key = csidh(params, &path, start, &mut rand::thread_rng());
         assert_eq!(
             public_key,
             ConstMontyForm::new(&Uint::from_be_hex(
@@ -194,7 +503,7 @@ mod tests {
             4, 2, 3, 5, 5, 5, 3, 0, 9, 6, 9, 8, 5, 5, 9, 2, 0, 3, 6,
         ];
         let start = ConstMontyForm::ZERO;
-        let public_key = csidh(params, path, start, &mut rand::thread_rng());
+        let public_key = csidh(params, &path, start, &mut rand::thread_rng());
         assert_eq!(
             public_key,
             ConstMontyForm::new(&Uint::from_be_hex(
@@ -215,7 +524,7 @@ mod tests {
             6, 10, 10, 6, 0, 5, 7, 4, 8, 10, 3, 6, 7, 2, 6, 4, 1, 8,
         ];
         let start = ConstMontyForm::ZERO;
-        let public_key = csidh(params, path, start, &mut rand::thread_rng());
+        let public_key = csidh(params, &path, start, &mut rand::thread_rng());
         assert_eq!(
             public_key,
             ConstMontyForm::new(&Uint::from_be_hex(
@@ -242,7 +551,7 @@ mod tests {
             E5C320A398F8E9987B66A9EB91BD1D749A0916C59080E7EC227B15E0F5A9BDFC41AE7927AA8A67D3289AE4\
             5FE06877D124420337CE90F6C3754186136684A533246E4A95BBB4C138342766729E79E7482E7AF355B31",
         ));
-        let public_key = csidh(params, path, start, &mut rand::thread_rng());
+        let public_key = csidh(params, &path, start, &mut rand::thread_rng());
         assert_eq!(
             public_key,
             ConstMontyForm::new(&Uint::from_be_hex(
@@ -268,7 +577,7 @@ mod tests {
             10,
         ];
         let start = ConstMontyForm::ZERO;
-        let public_key = csidh(params, path, start, &mut rand::thread_rng());
+        let public_key = csidh(params, &path, start, &mut rand::thread_rng());
         assert_eq!(
             public_key,
             ConstMontyForm::new(&Uint::from_be_hex(
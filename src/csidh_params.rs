@@ -1,15 +1,96 @@
+/// Builds the [`AdditionChains`](crate::field::AdditionChains) of the prime p such that
+/// (p - 1) / 2 is `$p_minus_1_over_2`, a constant `Uint<$limbs>`, at compile time.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! addition_chains {
+    ($limbs:expr, $p_minus_1_over_2:expr) => {{
+        use $crate::{AdditionChain, AdditionChains, Step, Uint};
+
+        const LEGENDRE_EXPONENT: Uint<{ $limbs }> = $p_minus_1_over_2;
+        const INVERSE_EXPONENT: Uint<{ $limbs }> =
+            LEGENDRE_EXPONENT.shl_vartime(1).wrapping_sub(&Uint::ONE);
+        const SQRT_EXPONENT: Uint<{ $limbs }> =
+            LEGENDRE_EXPONENT.wrapping_add(&Uint::ONE).shr_vartime(1);
+
+        const LEGENDRE: [Step; AdditionChain::len(&LEGENDRE_EXPONENT)] =
+            AdditionChain::steps(&LEGENDRE_EXPONENT);
+        const INVERSE: [Step; AdditionChain::len(&INVERSE_EXPONENT)] =
+            AdditionChain::steps(&INVERSE_EXPONENT);
+        const SQRT: [Step; AdditionChain::len(&SQRT_EXPONENT)] =
+            AdditionChain::steps(&SQRT_EXPONENT);
+
+        AdditionChains::new(
+            AdditionChain::new(&LEGENDRE),
+            AdditionChain::new(&INVERSE),
+            AdditionChain::new(&SQRT),
+        )
+    }};
+}
+
 /// Parameters for 1024 bits CSIDH
 pub mod csidh_1024;
 /// Parameters for 1792 bits CSIDH
 pub mod csidh_1792;
 /// Parameters for 512 bits CSIDH
 pub mod csidh_512;
+pub(crate) mod macros;
+pub(crate) mod primality;
+#[cfg(test)]
+pub(crate) mod toy;
+
+use core::cmp::Ordering;
 
 use crypto_bigint::{
-    Uint,
-    modular::{ConstMontyForm, ConstMontyParams},
+    CheckedMul, Integer, Uint,
+    modular::{ConstMontyForm, ConstMontyParams, MontyForm},
+};
+use zeroize::{DefaultIsZeroes, Zeroize};
+
+use crate::{
+    Error,
+    field::{AdditionChains, Field},
+    strategy::Strategy,
 };
 
+/// Smallest degree for which isogenies are computed with the square-root Vélu formulas, unless
+/// changed with [`CsidhParams::with_sqrt_velu_threshold`].
+///
+/// Vélu's formulas are computed without inversions, in a few multiplications per point of the
+/// kernel, so the overhead of the square-root Vélu formulas only pays off for large degrees. The
+/// threshold is the crossover measured in the literature for CSIDH-512.
+pub(crate) const SQRT_VELU_THRESHOLD: u64 = 89;
+
+/// Number of random points tried to validate a public key, unless changed with
+/// [`CsidhParams::with_validation_attempts`].
+///
+/// A random point of a supersingular curve has a large enough order to prove it in most cases, so
+/// that a supersingular curve is only rejected after all of them with a negligible probability.
+pub(crate) const VALIDATION_ATTEMPTS: u32 = 16;
+
+/// Strategy used to evaluate the class group action.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EvaluationMode {
+    /// Only computes the isogenies needed by the private key, plus dummy ones to hide the
+    /// exponents. Faster, but the control flow depends on the private key.
+    #[default]
+    VariableTime,
+    /// Computes the same sequence of operations whatever the private key. Real and dummy
+    /// isogenies are both fully computed, and one of the results is selected in constant time.
+    ConstantTime,
+    /// Constant-time evaluation without dummy isogenies, following <i>
+    /// <a href=https://eprint.iacr.org/2019/837>
+    /// [Cervantes-Vázquez, D., Chenu, M., Chi-Domínguez, J.-J., De Feo, L.,
+    /// Rodríguez-Henríquez, F., Smith, B.: Stronger and faster side-channel protections for CSIDH.
+    /// In: LATINCRYPT 2019, LNCS 11774. pp. 173–193. Springer (2019)]</a></i>.
+    ///
+    /// Every step computes an isogeny that is part of the result, which protects against fault
+    /// attacks on dummy isogenies. Isogenies are computed in both directions, so an exponent is
+    /// realized with as many steps as its bound only if both have the same parity. To keep the
+    /// key space of the other modes, the bounds are doubled: a key element bounded by b is even
+    /// and ranges from -2b to 2b, at the cost of 2b isogenies instead of b.
+    DummyFree,
+}
+
 /// Parameters of the CSIDH key exchange.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct CsidhParams<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>> {
@@ -17,6 +98,13 @@ pub struct CsidhParams<const LIMBS: usize, const N: usize, MOD: ConstMontyParams
     p_minus_1_over_2: Uint<LIMBS>,
     inverse_of_4: ConstMontyForm<MOD, LIMBS>,
     sqrt_of_p_times_4: Uint<LIMBS>,
+    bounds: [u32; N],
+    evaluation_mode: EvaluationMode,
+    sqrt_velu_threshold: u64,
+    validation_attempts: u32,
+    starting_curve_allowed: bool,
+    strategy: Strategy<N>,
+    addition_chains: Option<AdditionChains>,
 }
 
 impl<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>> CsidhParams<LIMBS, N, MOD> {
@@ -26,8 +114,10 @@ impl<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>> CsidhPara
     /// The caller is responsible for the validity of the
     /// parameters. Valid parameters respect the following rules:
     ///
-    /// - `lis` must be an array of mutually different prime numbers and contain the number 3.
-    ///   Their product, multiplied by 4, minus 1, must be a prime number that is called p.
+    /// - `lis` must be a nonempty array of mutually different odd prime numbers. Their product,
+    ///   multiplied by 4, minus 1, must be a prime number that is called p. Since the product is
+    ///   odd, p = 3 mod 8 whatever the primes, and 3 does not need to be one of them, as in
+    ///   [`CsidhParams::CSIDH_1792`].
     /// - `p_minus_1_over_2` must be equal to (p-1)/2.
     /// - `inverse_of_4` must be the inverse of 4 in the field of cardinality p.
     /// - `sqrt_of_p_times_4` must be (sqrt(p) * 4) rounded up.
@@ -42,6 +132,9 @@ impl<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>> CsidhPara
     /// **Use with care.**
     /// </div>
     ///
+    /// The [`csidh_params!`](crate::csidh_params) macro derives all of them from `lis` at compile
+    /// time instead.
+    ///
     /// # Example
     ///
     /// To construct the parameters from the prime numbers [3, 5, 7]:
@@ -76,17 +169,341 @@ impl<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>> CsidhPara
             p_minus_1_over_2,
             inverse_of_4,
             sqrt_of_p_times_4,
+            bounds: [5; N],
+            evaluation_mode: EvaluationMode::VariableTime,
+            sqrt_velu_threshold: SQRT_VELU_THRESHOLD,
+            validation_attempts: VALIDATION_ATTEMPTS,
+            starting_curve_allowed: true,
+            strategy: Strategy::optimal(&lis, SQRT_VELU_THRESHOLD),
+            addition_chains: None,
+        }
+    }
+
+    /// Constructs custom parameters, if they respect the rules of [`CsidhParams::new`].
+    ///
+    /// The rules are checked in the following order, and the error of the first one the parameters
+    /// break is returned:
+    ///
+    /// 1. The elements of `lis` are odd prime numbers ([`Error::InvalidPrime`]), distinct
+    ///    ([`Error::DuplicatePrime`]), and there is at least one ([`Error::MissingPrimes`]).
+    /// 2. The modulus of `MOD` is p = 4 ∏ ℓ<sub>i</sub> - 1 ([`Error::ModulusMismatch`]).
+    /// 3. p passes the Baillie-PSW primality test ([`Error::CompositeModulus`]), which no known
+    ///    composite number passes.
+    /// 4. `p_minus_1_over_2` is (p - 1) / 2 ([`Error::InvalidPMinus1Over2`]), `inverse_of_4` is the
+    ///    inverse of 4 modulo p ([`Error::InvalidInverseOf4`]), and `sqrt_of_p_times_4` is 4 √p
+    ///    rounded up ([`Error::InvalidSqrtOfPTimes4`]).
+    ///
+    /// Checking the primality of p takes a few exponentiations modulo p, so this is meant for
+    /// parameters loaded at runtime. Parameters known at compile time are better built with the
+    /// [`csidh_params!`](crate::csidh_params) macro.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csidh::{impl_modulus, ConstMontyForm, CsidhParams, Error, Uint};
+    ///
+    /// impl_modulus!(Prime419, Uint<1>, "00000000000001a3");
+    ///
+    /// let lis = [3, 5, 7];
+    /// let p_minus_1_over_2 = Uint::from(209u32);
+    /// let inverse_of_4: ConstMontyForm<Prime419, 1> = ConstMontyForm::new(&Uint::from(105u32));
+    ///
+    /// let params = CsidhParams::try_new(lis, p_minus_1_over_2, inverse_of_4, Uint::from(82u32));
+    /// assert!(params.is_ok());
+    ///
+    /// // 4 √419 is about 81.9, which rounds up to 82.
+    /// let params = CsidhParams::try_new(lis, p_minus_1_over_2, inverse_of_4, Uint::from(81u32));
+    /// assert_eq!(params, Err(Error::InvalidSqrtOfPTimes4));
+    /// ```
+    pub fn try_new(
+        lis: [u64; N],
+        p_minus_1_over_2: Uint<LIMBS>,
+        inverse_of_4: ConstMontyForm<MOD, LIMBS>,
+        sqrt_of_p_times_4: Uint<LIMBS>,
+    ) -> Result<Self, Error> {
+        check_lis(&lis)?;
+
+        let p = MOD::MODULUS.get();
+        let mut product = Uint::<LIMBS>::ONE;
+        for li in lis {
+            product = Option::from(product.checked_mul(&Uint::<LIMBS>::from(li)))
+                .ok_or(Error::ModulusMismatch)?;
+        }
+        if product.bits_vartime() + 2 > Uint::<LIMBS>::BITS
+            || product.shl_vartime(2).wrapping_sub(&Uint::ONE) != p
+        {
+            return Err(Error::ModulusMismatch);
+        }
+
+        if !primality::baillie_psw::<MontyForm<LIMBS>>(&MOD::MODULUS) {
+            return Err(Error::CompositeModulus);
+        }
+
+        if p_minus_1_over_2 != p.shr_vartime(1) {
+            return Err(Error::InvalidPMinus1Over2);
+        }
+        if inverse_of_4.retrieve() != product {
+            return Err(Error::InvalidInverseOf4);
+        }
+        // sqrt_of_p_times_4 is the smallest integer whose square is at least 16p.
+        let p_times_16 = (p.shl_vartime(4), p.shr_vartime(Uint::<LIMBS>::BITS - 4));
+        let below = sqrt_of_p_times_4.wrapping_sub(&Uint::ONE);
+        if sqrt_of_p_times_4 == Uint::ZERO
+            || compare_wide(sqrt_of_p_times_4.square_wide(), p_times_16).is_lt()
+            || compare_wide(below.square_wide(), p_times_16).is_ge()
+        {
+            return Err(Error::InvalidSqrtOfPTimes4);
+        }
+
+        Ok(Self::new(
+            lis,
+            p_minus_1_over_2,
+            inverse_of_4,
+            sqrt_of_p_times_4,
+        ))
+    }
+
+    /// Returns the same parameters, with private key elements bounded by `bounds`.
+    ///
+    /// The i-th element of a private key ranges from `-bounds[i]` to `bounds[i]`. Parameters
+    /// are constructed with a bound of 5 for every prime. Changing the bounds changes the key
+    /// space: use [`CsidhParams::key_space_bits`] to make sure it stays large enough.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a bound is greater than `i32::MAX / 2`, as bounds are doubled in
+    /// [`EvaluationMode::DummyFree`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csidh::CsidhParams;
+    ///
+    /// // Larger bounds for the smallest primes, whose isogenies are the cheapest.
+    /// let mut bounds = [5; 74];
+    /// bounds[..10].fill(8);
+    /// let params = CsidhParams::CSIDH_512.with_bounds(bounds);
+    /// assert_eq!(params.key_space_bits(), 262);
+    /// ```
+    #[must_use]
+    pub const fn with_bounds(self, bounds: [u32; N]) -> Self {
+        let mut i = 0;
+        while i < N {
+            assert!(
+                bounds[i] <= i32::MAX as u32 / 2,
+                "A doubled bound must fit in an i32"
+            );
+            i += 1;
+        }
+        Self { bounds, ..self }
+    }
+
+    /// Returns the bounds of the private key elements.
+    #[must_use]
+    pub const fn bounds(self) -> [u32; N] {
+        self.bounds
+    }
+
+    /// Returns the bounds of the private key elements in the evaluation mode of the parameters.
+    ///
+    /// They are the [`bounds`](Self::bounds), doubled in [`EvaluationMode::DummyFree`].
+    #[must_use]
+    pub const fn key_bounds(self) -> [u32; N] {
+        let mut bounds = self.bounds;
+        if matches!(self.evaluation_mode, EvaluationMode::DummyFree) {
+            let mut i = 0;
+            while i < N {
+                bounds[i] *= 2;
+                i += 1;
+            }
+        }
+        bounds
+    }
+
+    /// Returns the size of the private key space in bits, rounded down.
+    ///
+    /// It is the same in every [`EvaluationMode`], as [`EvaluationMode::DummyFree`] doubles the
+    /// bounds of the even key elements it allows.
+    #[must_use]
+    pub fn key_space_bits(self) -> u32 {
+        // Each factor fits in a u32, so the size fits in N limbs.
+        let mut size = [0u64; N];
+        if N > 0 {
+            size[0] = 1;
+        }
+        for bound in self.bounds {
+            let choices = 2 * u64::from(bound) + 1;
+            let mut carry = 0;
+            for limb in size.iter_mut() {
+                let wide = u128::from(*limb) * u128::from(choices) + u128::from(carry);
+                *limb = wide as u64;
+                carry = (wide >> 64) as u64;
+            }
+        }
+        size.iter()
+            .rposition(|&limb| limb != 0)
+            .map_or(0, |i| 64 * i as u32 + 63 - size[i].leading_zeros())
+    }
+
+    /// Returns the same parameters, evaluating the class group action with `evaluation_mode`.
+    ///
+    /// Parameters only differing by their evaluation mode compute the same public keys and
+    /// shared secrets.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csidh::{CsidhParams, EvaluationMode};
+    ///
+    /// let params = CsidhParams::CSIDH_512.with_evaluation_mode(EvaluationMode::ConstantTime);
+    /// ```
+    #[must_use]
+    pub const fn with_evaluation_mode(self, evaluation_mode: EvaluationMode) -> Self {
+        Self {
+            evaluation_mode,
+            ..self
+        }
+    }
+
+    /// Returns the strategy used to evaluate the class group action.
+    #[must_use]
+    pub const fn evaluation_mode(self) -> EvaluationMode {
+        self.evaluation_mode
+    }
+
+    /// Returns the same parameters, computing the isogenies of degree at least `threshold` with
+    /// the square-root Vélu formulas, from <i>
+    /// <a href=https://eprint.iacr.org/2020/341>
+    /// [Bernstein, D.J., De Feo, L., Leroux, A., Smith, B.: Faster computation of isogenies of
+    /// large prime degree. In: ANTS XIV, Open Book Series 4. pp. 39–55. MSP (2020)]</a></i>.
+    ///
+    /// They cost Õ(√ℓ) operations instead of O(ℓ) for an isogeny of degree ℓ, but only pay off
+    /// for large degrees. Smaller degrees are computed with Vélu's formulas. Both compute the same
+    /// results, so the threshold only changes the execution time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csidh::CsidhParams;
+    ///
+    /// // Only use Vélu's formulas.
+    /// let params = CsidhParams::CSIDH_1024.with_sqrt_velu_threshold(u64::MAX);
+    /// ```
+    #[must_use]
+    pub const fn with_sqrt_velu_threshold(self, threshold: u64) -> Self {
+        Self {
+            sqrt_velu_threshold: threshold,
+            strategy: Strategy::optimal(&self.lis, threshold),
+            ..self
+        }
+    }
+
+    /// Returns the smallest degree for which isogenies are computed with the square-root Vélu
+    /// formulas.
+    #[must_use]
+    pub const fn sqrt_velu_threshold(self) -> u64 {
+        self.sqrt_velu_threshold
+    }
+
+    /// Returns the same parameters, trying up to `attempts` random points to validate a public key.
+    ///
+    /// Each attempt either proves the curve of the key supersingular or ordinary, or is
+    /// inconclusive when the random point has a small order. Keys are rejected after `attempts`
+    /// inconclusive attempts. Parameters are constructed with 16 attempts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `attempts` is 0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csidh::CsidhParams;
+    ///
+    /// let params = CsidhParams::CSIDH_512.with_validation_attempts(4);
+    /// ```
+    #[must_use]
+    pub const fn with_validation_attempts(self, attempts: u32) -> Self {
+        assert!(attempts > 0, "At least one validation attempt is needed");
+        Self {
+            validation_attempts: attempts,
+            ..self
+        }
+    }
+
+    /// Returns the maximum number of random points tried to validate a public key.
+    #[must_use]
+    pub const fn validation_attempts(self) -> u32 {
+        self.validation_attempts
+    }
+
+    /// Returns the same parameters, accepting the starting curve A = 0 as a public key only if
+    /// `allowed` is true.
+    ///
+    /// The starting curve is a valid public key, that of the all-zero private key, and is accepted
+    /// by default. Protocols that forbid it can reject it with
+    /// [`Error::StartingCurve`](crate::Error::StartingCurve).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csidh::{CsidhParams, Error, PublicKey, Uint};
+    ///
+    /// let mut rng = rand::thread_rng();
+    /// let params = CsidhParams::CSIDH_512.with_starting_curve_allowed(false);
+    /// assert_eq!(
+    ///     PublicKey::try_new(params, Uint::ZERO, &mut rng),
+    ///     Err(Error::StartingCurve)
+    /// );
+    /// ```
+    #[must_use]
+    pub const fn with_starting_curve_allowed(self, allowed: bool) -> Self {
+        Self {
+            starting_curve_allowed: allowed,
+            ..self
+        }
+    }
+
+    /// Returns whether the starting curve A = 0 is accepted as a public key.
+    #[must_use]
+    pub const fn starting_curve_allowed(self) -> bool {
+        self.starting_curve_allowed
+    }
+
+    /// Returns the same parameters, computing exponentiations in the base field with the fixed
+    /// `addition_chains`.
+    ///
+    /// Built-in parameters come with their addition chains. Without them, exponentiations use
+    /// square-and-multiply.
+    ///
+    /// <div class="warning">
+    /// The chains must be built from the prime of the parameters, or the results are wrong.
+    /// </div>
+    #[must_use]
+    pub const fn with_addition_chains(self, addition_chains: AdditionChains) -> Self {
+        Self {
+            addition_chains: Some(addition_chains),
+            ..self
         }
     }
 
+    /// Returns the addition chains used for exponentiations in the base field, if any.
+    #[must_use]
+    pub const fn addition_chains(self) -> Option<AdditionChains> {
+        self.addition_chains
+    }
+
+    #[cfg(test)]
     pub(crate) const fn lis(self) -> [u64; N] {
         self.lis
     }
 
+    #[cfg(test)]
     pub(crate) const fn p_minus_1_over_2(self) -> Uint<LIMBS> {
         self.p_minus_1_over_2
     }
 
+    #[cfg(test)]
     pub(crate) const fn inverse_of_4(self) -> ConstMontyForm<MOD, LIMBS> {
         self.inverse_of_4
     }
@@ -94,4 +511,306 @@ impl<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>> CsidhPara
     pub(crate) const fn sqrt_of_p_times_4(self) -> Uint<LIMBS> {
         self.sqrt_of_p_times_4
     }
+
+    /// Returns a 32-bit fingerprint of the parameters, computed as the FNV-1a hash of the
+    /// little-endian bytes of `lis` followed by those of `bounds`, and of a last byte in
+    /// [`EvaluationMode::DummyFree`], whose keys are encoded differently.
+    ///
+    /// It is embedded in encoded private keys so that a key cannot be decoded with parameters
+    /// it was not generated for.
+    pub(crate) const fn tag(self) -> u32 {
+        let mut hash: u32 = 0x811c_9dc5;
+        let mut i = 0;
+        while i < N {
+            let bytes = self.lis[i].to_le_bytes();
+            let mut j = 0;
+            while j < bytes.len() {
+                hash ^= bytes[j] as u32;
+                hash = hash.wrapping_mul(0x0100_0193);
+                j += 1;
+            }
+            i += 1;
+        }
+        let mut i = 0;
+        while i < N {
+            let bytes = self.bounds[i].to_le_bytes();
+            let mut j = 0;
+            while j < bytes.len() {
+                hash ^= bytes[j] as u32;
+                hash = hash.wrapping_mul(0x0100_0193);
+                j += 1;
+            }
+            i += 1;
+        }
+        if matches!(self.evaluation_mode, EvaluationMode::DummyFree) {
+            hash ^= 1;
+            hash = hash.wrapping_mul(0x0100_0193);
+        }
+        hash
+    }
+}
+
+/// Parameters the class group action is evaluated with.
+///
+/// The curve arithmetic and the evaluations of the action are written once against this trait,
+/// which gives the same view of [`CsidhParams`], whose sizes are known at compile time, and of
+/// [`DynCsidhParams`](crate::DynCsidhParams), whose sizes are chosen at runtime. Arrays have one
+/// element per prime.
+pub trait Params: Copy {
+    /// Element of the base field F<sub>p</sub>.
+    type Element: Field;
+
+    /// Integer with the precision of p.
+    type Integer: Integer;
+
+    /// Array with one element per prime.
+    type Array<T>: AsRef<[T]> + AsMut<[T]>;
+
+    /// Array with one element per prime, for working copies of secret values, wiped with
+    /// [`Zeroize`].
+    type SecretArray<T: DefaultIsZeroes>: AsRef<[T]> + AsMut<[T]> + Zeroize;
+
+    /// Returns an array filled with `value`.
+    fn array<T: Clone>(&self, value: T) -> Self::Array<T>;
+
+    /// Returns an array filled with the default value of `T`, which is zero.
+    fn secret_array<T: DefaultIsZeroes>(&self) -> Self::SecretArray<T>;
+
+    /// Returns the prime numbers ℓ<sub>i</sub>.
+    fn lis(&self) -> &[u64];
+
+    /// Returns the bounds of the private key elements, before they are doubled in
+    /// [`EvaluationMode::DummyFree`].
+    fn bounds(&self) -> &[u32];
+
+    /// Returns the strategy used to evaluate the class group action.
+    fn evaluation_mode(&self) -> EvaluationMode;
+
+    /// Returns the smallest degree for which isogenies are computed with the square-root Vélu
+    /// formulas.
+    fn sqrt_velu_threshold(&self) -> u64;
+
+    /// Returns the maximum number of random points tried to validate a public key.
+    fn validation_attempts(&self) -> u32;
+
+    /// Returns the splits of the optimal strategies, as stored in [`Strategy`].
+    fn splits(&self) -> &[u16];
+
+    /// Returns the addition chains used for exponentiations in the base field, if any.
+    fn addition_chains(&self) -> Option<AdditionChains>;
+
+    /// Returns the prime p.
+    fn p(&self) -> Self::Integer;
+
+    /// Returns 4√p rounded up.
+    fn sqrt_of_p_times_4(&self) -> &Self::Integer;
+
+    /// Returns the inverse of 4 modulo p.
+    fn inverse_of_4(&self) -> Self::Element;
+
+    /// Returns `x` with the precision of p.
+    fn integer(&self, x: u64) -> Self::Integer;
+}
+
+impl<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>> Params
+    for CsidhParams<LIMBS, N, MOD>
+{
+    type Element = ConstMontyForm<MOD, LIMBS>;
+    type Integer = Uint<LIMBS>;
+    type Array<T> = [T; N];
+    type SecretArray<T: DefaultIsZeroes> = [T; N];
+
+    fn array<T: Clone>(&self, value: T) -> [T; N] {
+        core::array::from_fn(|_| value.clone())
+    }
+
+    fn secret_array<T: DefaultIsZeroes>(&self) -> [T; N] {
+        [T::default(); N]
+    }
+
+    fn lis(&self) -> &[u64] {
+        &self.lis
+    }
+
+    fn bounds(&self) -> &[u32] {
+        &self.bounds
+    }
+
+    fn evaluation_mode(&self) -> EvaluationMode {
+        self.evaluation_mode
+    }
+
+    fn sqrt_velu_threshold(&self) -> u64 {
+        self.sqrt_velu_threshold
+    }
+
+    fn validation_attempts(&self) -> u32 {
+        self.validation_attempts
+    }
+
+    fn splits(&self) -> &[u16] {
+        self.strategy.splits()
+    }
+
+    fn addition_chains(&self) -> Option<AdditionChains> {
+        self.addition_chains
+    }
+
+    fn p(&self) -> Uint<LIMBS> {
+        MOD::MODULUS.get()
+    }
+
+    fn sqrt_of_p_times_4(&self) -> &Uint<LIMBS> {
+        &self.sqrt_of_p_times_4
+    }
+
+    fn inverse_of_4(&self) -> ConstMontyForm<MOD, LIMBS> {
+        self.inverse_of_4
+    }
+
+    fn integer(&self, x: u64) -> Uint<LIMBS> {
+        Uint::from(x)
+    }
+}
+
+/// Checks that `lis` are distinct odd prime numbers, and that there is at least one.
+pub(crate) const fn check_lis(lis: &[u64]) -> Result<(), Error> {
+    let mut i = 0;
+    while i < lis.len() {
+        if lis[i] == 2 || !primality::is_prime(lis[i]) {
+            return Err(Error::InvalidPrime);
+        }
+        let mut j = 0;
+        while j < i {
+            if lis[i] == lis[j] {
+                return Err(Error::DuplicatePrime);
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    if lis.is_empty() {
+        Err(Error::MissingPrimes)
+    } else {
+        Ok(())
+    }
+}
+
+/// Compares two integers given as their (low, high) halves.
+fn compare_wide<const LIMBS: usize>(
+    a: (Uint<LIMBS>, Uint<LIMBS>),
+    b: (Uint<LIMBS>, Uint<LIMBS>),
+) -> Ordering {
+    a.1.cmp(&b.1).then(a.0.cmp(&b.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use crypto_bigint::impl_modulus;
+
+    use super::*;
+
+    #[test]
+    fn key_space_bits() {
+        let params = CsidhParams::CSIDH_512;
+        // log2(11^74) is about 255.998.
+        assert_eq!(params.key_space_bits(), 255);
+        let dummy_free = params.with_evaluation_mode(EvaluationMode::DummyFree);
+        assert_eq!(dummy_free.key_bounds(), [10; 74]);
+        assert_eq!(dummy_free.key_space_bits(), 255);
+        // log2(3^73) is about 115.702.
+        let mut bounds = [1; 74];
+        bounds[0] = 0;
+        assert_eq!(params.with_bounds(bounds).key_space_bits(), 115);
+    }
+
+    /// Calls [`CsidhParams::try_new`] with the components of `params`.
+    fn try_new<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>>(
+        params: CsidhParams<LIMBS, N, MOD>,
+    ) -> Result<CsidhParams<LIMBS, N, MOD>, Error> {
+        CsidhParams::try_new(
+            params.lis(),
+            params.p_minus_1_over_2(),
+            params.inverse_of_4(),
+            params.sqrt_of_p_times_4(),
+        )
+    }
+
+    #[test]
+    fn try_new_accepts_valid_params() {
+        assert_eq!(try_new(toy::TOY), Ok(toy::TOY));
+        assert!(try_new(CsidhParams::CSIDH_512).is_ok());
+        assert!(try_new(CsidhParams::CSIDH_1024).is_ok());
+        // CSIDH-1792 does not use 3, which is not needed for p = 3 mod 8.
+        assert!(try_new(CsidhParams::CSIDH_1792).is_ok());
+    }
+
+    #[test]
+    fn try_new_names_the_violated_rule() {
+        let params = toy::TOY;
+        let p_minus_1_over_2 = params.p_minus_1_over_2();
+        let inverse_of_4 = params.inverse_of_4();
+        let sqrt_of_p_times_4 = params.sqrt_of_p_times_4();
+        let check = |lis, p_minus_1_over_2, inverse_of_4, sqrt_of_p_times_4| {
+            CsidhParams::<{ toy::LIMBS }, { toy::N }, toy::MOD>::try_new(
+                lis,
+                p_minus_1_over_2,
+                inverse_of_4,
+                sqrt_of_p_times_4,
+            )
+            .map(|_| ())
+        };
+
+        let mut lis = params.lis();
+        lis[3] = 9;
+        assert_eq!(
+            check(lis, p_minus_1_over_2, inverse_of_4, sqrt_of_p_times_4),
+            Err(Error::InvalidPrime)
+        );
+        lis[3] = 2;
+        assert_eq!(
+            check(lis, p_minus_1_over_2, inverse_of_4, sqrt_of_p_times_4),
+            Err(Error::InvalidPrime)
+        );
+        lis[3] = 5;
+        assert_eq!(
+            check(lis, p_minus_1_over_2, inverse_of_4, sqrt_of_p_times_4),
+            Err(Error::DuplicatePrime)
+        );
+        lis = params.lis();
+        lis[10] = 41;
+        assert_eq!(
+            check(lis, p_minus_1_over_2, inverse_of_4, sqrt_of_p_times_4),
+            Err(Error::ModulusMismatch)
+        );
+
+        let one = Uint::ONE;
+        let lis = params.lis();
+        assert_eq!(
+            check(lis, p_minus_1_over_2 + one, inverse_of_4, sqrt_of_p_times_4),
+            Err(Error::InvalidPMinus1Over2)
+        );
+        assert_eq!(
+            check(lis, p_minus_1_over_2, -inverse_of_4, sqrt_of_p_times_4),
+            Err(Error::InvalidInverseOf4)
+        );
+        assert_eq!(
+            check(lis, p_minus_1_over_2, inverse_of_4, sqrt_of_p_times_4 - one),
+            Err(Error::InvalidSqrtOfPTimes4)
+        );
+        assert_eq!(
+            check(lis, p_minus_1_over_2, inverse_of_4, sqrt_of_p_times_4 + one),
+            Err(Error::InvalidSqrtOfPTimes4)
+        );
+
+        // 4 * 3 * 5 * 7 * 11 - 1 = 4619 = 31 * 149.
+        impl_modulus!(Composite, crypto_bigint::U64, "000000000000120b");
+        let params = CsidhParams::<{ toy::LIMBS }, 4, Composite>::try_new(
+            [3, 5, 7, 11],
+            Uint::from(2309u32),
+            ConstMontyForm::new(&Uint::from(1155u32)),
+            Uint::from(272u32),
+        );
+        assert_eq!(params, Err(Error::CompositeModulus));
+    }
 }
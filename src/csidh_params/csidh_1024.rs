@@ -19,10 +19,17 @@ impl_modulus!(
     887b195464e27f705ddda97ccd65fb43ab68a754ddd05e9766449e1a607eb0c632468597d98552f29c18b"
 );
 
+/// (p - 1) / 2
+const P_MINUS_1_OVER_2: Uint<LIMBS> = Uint::from_be_hex(
+    "0865911d704be78d290b38147936c7c368785088758573a15907a9a7331d609ef06c17b647d0afb790d51\
+        5f1f09440acf2008d7927fbe3b644df432518b06378178692bb2321251a311e4996aeba52e01748c13caaa\
+        443d8caa32713fb82eeed4be66b2fda1d5b453aa6ee82f4bb3224f0d303f5863192342cbecc2a9794e0c5",
+);
+
 impl CsidhParams<LIMBS, N, MOD> {
     /// A proposition for CSIDH-1024.
-    pub const CSIDH_1024: CsidhParams<LIMBS, N, MOD> = CsidhParams {
-        lis: [
+    pub const CSIDH_1024: CsidhParams<LIMBS, N, MOD> = CsidhParams::new(
+        [
             3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83,
             89, 97, 101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173, 179,
             181, 191, 193, 197, 199, 211, 223, 227, 229, 233, 239, 241, 251, 257, 263, 269, 271,
@@ -32,20 +39,17 @@ impl CsidhParams<LIMBS, N, MOD> {
             601, 607, 613, 617, 619, 631, 641, 643, 647, 653, 659, 661, 673, 677, 683, 691, 701,
             709, 719, 727, 863, 947,
         ],
-        p_minus_1_over_2: Uint::from_be_hex(
-            "0865911d704be78d290b38147936c7c368785088758573a15907a9a7331d609ef06c17b647d0afb790d51\
-            5f1f09440acf2008d7927fbe3b644df432518b06378178692bb2321251a311e4996aeba52e01748c13caaa\
-            443d8caa32713fb82eeed4be66b2fda1d5b453aa6ee82f4bb3224f0d303f5863192342cbecc2a9794e0c5",
-        ),
-        inverse_of_4: ConstMontyForm::new(&Uint::from_be_hex(
+        P_MINUS_1_OVER_2,
+        ConstMontyForm::new(&Uint::from_be_hex(
             "0432c88eb825f3c694859c0a3c9b63e1b43c28443ac2b9d0ac83d4d3998eb04f78360bdb23e857dbc86a8\
             af8f84a2056790046bc93fdf1db226fa1928c5831bc0bc3495d9190928d188f24cb575d29700ba4609e555\
             221ec65519389fdc17776a5f33597ed0eada29d5377417a5d9912786981fac318c91a165f66154bca7063",
         )),
-        sqrt_of_p_times_4: Uint::from_be_hex(
+        Uint::from_be_hex(
             "0000000000000000000000000000000000000000000000000000000000000000000000000000000000000\
             0000000000000000000000000000000000000000001064567fe71623dd3d0453e10c2330470580e0f1f224\
             d70507fc43905ea5cc3705f413e8c164007037e08e352ae20804b82c7ef4aff3cfc5df5a41fa2c58c6fd4",
         ),
-    };
+    )
+    .with_addition_chains(addition_chains!(LIMBS, P_MINUS_1_OVER_2));
 }
@@ -0,0 +1,646 @@
+//! Derivation of the parameters from their prime numbers alone, at compile time.
+
+use crypto_bigint::{Limb, Odd, Uint};
+
+use super::{
+    check_lis,
+    primality::{is_prime, jacobi_of_discriminant_from_rem},
+};
+use crate::Error;
+
+/// Number of 64-bit words of the integers the parameters are derived with, enough for primes p of
+/// up to 8066 bits.
+const WIDE_WORDS: usize = 128;
+
+/// Number of limbs of [`WIDE_WORDS`] words.
+const WIDE_LIMBS: usize = WIDE_WORDS * 64 / Limb::BITS as usize;
+
+/// Defines a module holding the CSIDH parameters built from the prime numbers ℓ<sub>i</sub>, and
+/// optionally from the bounds of the private key elements.
+///
+/// Every constant is derived at compile time: the prime p = 4 ∏ ℓ<sub>i</sub> - 1, the modulus
+/// type `MOD` that stands for it, the smallest number of limbs `LIMBS` that stores integers up to
+/// p, the number of primes `N`, and the other constants [`CsidhParams::new`] asks for. The
+/// parameters come with their [`AdditionChains`](crate::AdditionChains), like the built-in ones.
+/// The module holds them in `PARAMS`.
+///
+/// Compilation fails if the ℓ<sub>i</sub> are not distinct odd primes, or if p is not prime. The
+/// primality of p is checked with the Baillie-PSW test, like [`CsidhParams::try_new`] does, whose
+/// cost grows with the cube of the size of p: it takes seconds for a 1024-bit prime, and minutes
+/// for a 4096-bit one.
+///
+/// # Example
+///
+/// ```
+/// use csidh::{PrivateKey, PublicKey, SharedSecret};
+///
+/// csidh::csidh_params!(
+///     /// Parameters for the prime 419 = 4 * 3 * 5 * 7 - 1.
+///     mod csidh_419,
+///     [3, 5, 7],
+///     bounds = [2, 2, 1]
+/// );
+///
+/// let mut rng = rand::thread_rng();
+/// let alice = PrivateKey::random(csidh_419::PARAMS, &mut rng);
+/// let bob = PrivateKey::random(csidh_419::PARAMS, &mut rng);
+/// let alice_public_key = PublicKey::from(&alice, &mut rng);
+/// let bob_public_key = PublicKey::from(&bob, &mut rng);
+/// assert_eq!(
+///     SharedSecret::from(bob_public_key, &alice, &mut rng),
+///     SharedSecret::from(alice_public_key, &bob, &mut rng)
+/// );
+/// ```
+///
+/// 4 * 3 * 5 * 7 * 11 - 1 = 31 * 149 is not prime:
+///
+/// ```compile_fail
+/// csidh::csidh_params!(mod composite, [3, 5, 7, 11]);
+/// let _ = composite::PARAMS;
+/// ```
+///
+/// 4 * 3 * 120091 - 1 = 347 * 4153 passes the Miller-Rabin test in base 2, but not the strong
+/// Lucas test:
+///
+/// ```compile_fail
+/// csidh::csidh_params!(mod pseudoprime, [3, 120091]);
+/// let _ = pseudoprime::PARAMS;
+/// ```
+///
+/// 4 * 3 * 3 * 5 - 1 = 179 is prime, but 3 appears twice:
+///
+/// ```compile_fail
+/// csidh::csidh_params!(mod duplicate, [3, 3, 5]);
+/// let _ = duplicate::PARAMS;
+/// ```
+///
+/// [`CsidhParams::new`]: crate::CsidhParams::new
+/// [`CsidhParams::try_new`]: crate::CsidhParams::try_new
+#[macro_export]
+macro_rules! csidh_params {
+    (
+        $(#[$attr:meta])*
+        $vis:vis mod $name:ident,
+        [$($li:expr),+ $(,)?]
+        $(, bounds = $bounds:expr)?
+        $(,)?
+    ) => {
+        $(#[$attr])*
+        $vis mod $name {
+            /// Number of prime numbers used by the parameters
+            pub const N: usize = [$($li),+].len();
+
+            const LIS: [u64; N] = [$($li),+];
+
+            /// Number of limbs used to store public keys and shared secrets
+            pub const LIMBS: usize = $crate::__private::limbs(&LIS);
+
+            /// The prime p = 4 ∏ ℓ<sub>i</sub> - 1
+            #[allow(clippy::upper_case_acronyms)]
+            #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+            pub struct MOD;
+
+            impl $crate::__private::ConstMontyParams<LIMBS> for MOD {
+                const LIMBS: usize = LIMBS;
+                const MODULUS: $crate::__private::Odd<$crate::Uint<LIMBS>> =
+                    $crate::__private::modulus(&LIS);
+                const ONE: $crate::Uint<LIMBS> = $crate::Uint::MAX
+                    .rem_vartime(Self::MODULUS.as_nz_ref())
+                    .wrapping_add(&$crate::Uint::ONE);
+                const R2: $crate::Uint<LIMBS> = $crate::Uint::rem_wide_vartime(
+                    Self::ONE.square_wide(),
+                    Self::MODULUS.as_nz_ref(),
+                );
+                const MOD_NEG_INV: $crate::__private::Limb = $crate::__private::mod_neg_inv(
+                    Self::MODULUS.as_ref(),
+                );
+                const MOD_LEADING_ZEROS: u32 = $crate::__private::mod_leading_zeros(
+                    Self::MODULUS.as_ref(),
+                );
+                const R3: $crate::Uint<LIMBS> = $crate::__private::montgomery_reduction(
+                    &Self::R2.square_wide(),
+                    &Self::MODULUS,
+                    Self::MOD_NEG_INV,
+                );
+            }
+
+            #[allow(long_running_const_eval)]
+            const P_MINUS_1_OVER_2: $crate::Uint<LIMBS> = {
+                assert!(
+                    $crate::__private::is_probable_prime(&LIS),
+                    "4 times the product of the primes, minus 1, must be prime"
+                );
+                $crate::__private::p_minus_1_over_2(&LIS)
+            };
+
+            /// The parameters
+            pub const PARAMS: $crate::CsidhParams<LIMBS, N, MOD> = $crate::CsidhParams::new(
+                LIS,
+                P_MINUS_1_OVER_2,
+                $crate::ConstMontyForm::new(&$crate::__private::product(&LIS)),
+                $crate::__private::sqrt_of_p_times_4(&LIS),
+            )
+            .with_addition_chains($crate::addition_chains!(LIMBS, P_MINUS_1_OVER_2))
+            $(.with_bounds($bounds))?;
+        }
+    };
+}
+
+/// Integer of up to [`WIDE_WORDS`] little-endian 64-bit words, with a minimal arithmetic that is
+/// cheap to evaluate at compile time.
+#[derive(Clone, Copy)]
+struct Wide {
+    words: [u64; WIDE_WORDS],
+    len: usize,
+}
+
+impl Wide {
+    /// Returns ∏ ℓ<sub>i</sub>, after checking `lis`.
+    const fn product(lis: &[u64]) -> Self {
+        match check_lis(lis) {
+            Ok(()) => {}
+            Err(Error::InvalidPrime) => panic!("The primes must be odd prime numbers"),
+            Err(Error::DuplicatePrime) => panic!("The primes must be distinct"),
+            Err(_) => panic!("There must be at least one prime"),
+        }
+        let mut product = Self {
+            words: [0; WIDE_WORDS],
+            len: 1,
+        };
+        product.words[0] = 1;
+        let mut i = 0;
+        while i < lis.len() {
+            let mut carry = 0;
+            let mut j = 0;
+            while j < product.len {
+                let wide = product.words[j] as u128 * lis[i] as u128 + carry;
+                product.words[j] = wide as u64;
+                carry = wide >> 64;
+                j += 1;
+            }
+            if carry != 0 {
+                // p = 4 ∏ ℓ - 1 and 16p, whose square root is needed, must fit.
+                assert!(
+                    product.len < WIDE_WORDS - 2,
+                    "The product of the primes is too large"
+                );
+                product.words[product.len] = carry as u64;
+                product.len += 1;
+            }
+            i += 1;
+        }
+        product
+    }
+
+    /// Returns p = 4 ∏ ℓ<sub>i</sub> - 1.
+    const fn modulus(lis: &[u64]) -> Self {
+        let mut p = Self::product(lis);
+        p.words[p.len] = 0;
+        p.len += 1;
+        let mut j = p.len - 1;
+        while j > 0 {
+            p.words[j] = (p.words[j] << 2) | (p.words[j - 1] >> 62);
+            j -= 1;
+        }
+        // ∏ ℓ is odd, so the lowest word of 4 ∏ ℓ is not zero and subtracting 1 does not borrow.
+        p.words[0] = (p.words[0] << 2) - 1;
+        if p.words[p.len - 1] == 0 {
+            p.len -= 1;
+        }
+        p
+    }
+
+    /// Returns the number of bits of the integer.
+    const fn bits(&self) -> u32 {
+        let top = self.words[self.len - 1];
+        64 * (self.len as u32 - 1) + u64::BITS - top.leading_zeros()
+    }
+
+    /// Returns the integer as a `Uint<LIMBS>`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the integer does not fit.
+    const fn to_uint<const LIMBS: usize>(self) -> Uint<LIMBS> {
+        let mut bytes = [0u8; WIDE_WORDS * 8];
+        let mut j = 0;
+        while j < bytes.len() {
+            bytes[j] = (self.words[j / 8] >> (8 * (j % 8))) as u8;
+            j += 1;
+        }
+        let (bytes, rest) = bytes.split_at(Uint::<LIMBS>::BYTES);
+        let mut j = 0;
+        while j < rest.len() {
+            assert!(rest[j] == 0, "The integer does not fit");
+            j += 1;
+        }
+        Uint::from_le_slice(bytes)
+    }
+
+    /// Returns whether `self` is at least `other`, both having `len` words.
+    const fn ge(&self, other: &Self, len: usize) -> bool {
+        let mut j = len;
+        while j > 0 {
+            j -= 1;
+            if self.words[j] != other.words[j] {
+                return self.words[j] > other.words[j];
+            }
+        }
+        true
+    }
+
+    /// Subtracts `other` from `self`, both having `len` words, and returns the final borrow.
+    const fn sub_assign(&mut self, other: &Self, len: usize) -> bool {
+        let mut borrow = 0;
+        let mut j = 0;
+        while j < len {
+            let (difference, borrow_1) = self.words[j].overflowing_sub(other.words[j]);
+            let (difference, borrow_2) = difference.overflowing_sub(borrow);
+            self.words[j] = difference;
+            borrow = (borrow_1 | borrow_2) as u64;
+            j += 1;
+        }
+        borrow != 0
+    }
+
+    /// Adds `other` to `self`, both having `len` words, and returns the final carry.
+    const fn add_assign(&mut self, other: &Self, len: usize) -> bool {
+        let mut carry = 0;
+        let mut j = 0;
+        while j < len {
+            let (sum, carry_1) = self.words[j].overflowing_add(other.words[j]);
+            let (sum, carry_2) = sum.overflowing_add(carry);
+            self.words[j] = sum;
+            carry = (carry_1 | carry_2) as u64;
+            j += 1;
+        }
+        carry != 0
+    }
+
+    /// Returns whether `self` and `other`, both having `len` words, are equal.
+    const fn eq(&self, other: &Self, len: usize) -> bool {
+        let mut j = 0;
+        while j < len {
+            if self.words[j] != other.words[j] {
+                return false;
+            }
+            j += 1;
+        }
+        true
+    }
+
+    /// Returns whether the bit of index `bit` is set.
+    const fn bit(&self, bit: u32) -> bool {
+        (self.words[bit as usize / 64] >> (bit % 64)) & 1 == 1
+    }
+
+    /// Returns the integer modulo `d`.
+    const fn rem(&self, d: u64) -> u64 {
+        let mut rem = 0;
+        let mut j = self.len;
+        while j > 0 {
+            j -= 1;
+            rem = ((rem << 64) | self.words[j] as u128) % d as u128;
+        }
+        rem as u64
+    }
+
+    /// Returns `self` + `other` modulo `p`, both being reduced.
+    const fn add_mod(&self, other: &Self, p: &Self) -> Self {
+        let mut sum = *self;
+        if sum.add_assign(other, p.len) || sum.ge(p, p.len) {
+            sum.sub_assign(p, p.len);
+        }
+        sum
+    }
+
+    /// Returns `self` - `other` modulo `p`, both being reduced.
+    const fn sub_mod(&self, other: &Self, p: &Self) -> Self {
+        let mut difference = *self;
+        if difference.sub_assign(other, p.len) {
+            difference.add_assign(p, p.len);
+        }
+        difference
+    }
+
+    /// Returns `self` / 2 modulo `p`, `self` being reduced.
+    const fn half_mod(&self, p: &Self) -> Self {
+        let mut half = *self;
+        let mut carry = 0;
+        if half.words[0] & 1 == 1 {
+            carry = half.add_assign(p, p.len) as u64;
+        }
+        let mut j = 0;
+        while j < p.len {
+            let high = if j + 1 < p.len {
+                half.words[j + 1]
+            } else {
+                carry
+            };
+            half.words[j] = (half.words[j] >> 1) | (high << 63);
+            j += 1;
+        }
+        half
+    }
+
+    /// Doubles `self` modulo `p`, `self` being reduced.
+    const fn double_mod(&mut self, p: &Self) {
+        let mut carry = 0;
+        let mut j = 0;
+        while j < p.len {
+            let word = self.words[j];
+            self.words[j] = (word << 1) | carry;
+            carry = word >> 63;
+            j += 1;
+        }
+        if carry != 0 || self.ge(p, p.len) {
+            self.sub_assign(p, p.len);
+        }
+    }
+
+    /// Returns `self` * `other` / 2<sup>64 * `p.len`</sup> modulo `p`, both being reduced, with
+    /// the Montgomery reduction. `p_inv` is -1 / p modulo 2<sup>64</sup>.
+    const fn monty_mul(&self, other: &Self, p: &Self, p_inv: u64) -> Self {
+        let len = p.len;
+        let mut t = [0u64; WIDE_WORDS + 2];
+        let mut i = 0;
+        while i < len {
+            let mut carry = 0;
+            let mut j = 0;
+            while j < len {
+                let wide = self.words[j] as u128 * other.words[i] as u128 + t[j] as u128 + carry;
+                t[j] = wide as u64;
+                carry = wide >> 64;
+                j += 1;
+            }
+            let wide = t[len] as u128 + carry;
+            t[len] = wide as u64;
+            t[len + 1] = (wide >> 64) as u64;
+
+            let m = t[0].wrapping_mul(p_inv);
+            let mut carry = (m as u128 * p.words[0] as u128 + t[0] as u128) >> 64;
+            let mut j = 1;
+            while j < len {
+                let wide = m as u128 * p.words[j] as u128 + t[j] as u128 + carry;
+                t[j - 1] = wide as u64;
+                carry = wide >> 64;
+                j += 1;
+            }
+            let wide = t[len] as u128 + carry;
+            t[len - 1] = wide as u64;
+            t[len] = t[len + 1] + (wide >> 64) as u64;
+            i += 1;
+        }
+
+        let mut result = Self {
+            words: [0; WIDE_WORDS],
+            len,
+        };
+        let mut j = 0;
+        while j < len {
+            result.words[j] = t[j];
+            j += 1;
+        }
+        if t[len] != 0 || result.ge(p, len) {
+            result.sub_assign(p, len);
+        }
+        result
+    }
+}
+
+/// Returns the smallest number of limbs that stores integers up to p.
+#[doc(hidden)]
+pub const fn limbs(lis: &[u64]) -> usize {
+    Wide::modulus(lis).bits().div_ceil(Limb::BITS) as usize
+}
+
+/// Returns ∏ ℓ<sub>i</sub>, which is the inverse of 4 modulo p.
+#[doc(hidden)]
+pub const fn product<const LIMBS: usize>(lis: &[u64]) -> Uint<LIMBS> {
+    Wide::product(lis).to_uint()
+}
+
+/// Returns p.
+#[doc(hidden)]
+pub const fn modulus<const LIMBS: usize>(lis: &[u64]) -> Odd<Uint<LIMBS>> {
+    Wide::modulus(lis)
+        .to_uint::<LIMBS>()
+        .to_odd()
+        .expect("p is odd")
+}
+
+/// Returns (p - 1) / 2 = 2 ∏ ℓ<sub>i</sub> - 1.
+#[doc(hidden)]
+pub const fn p_minus_1_over_2<const LIMBS: usize>(lis: &[u64]) -> Uint<LIMBS> {
+    modulus::<LIMBS>(lis).as_ref().shr_vartime(1)
+}
+
+/// Returns 4 √p rounded up, as the square root of 16p rounded up.
+#[doc(hidden)]
+pub const fn sqrt_of_p_times_4<const LIMBS: usize>(lis: &[u64]) -> Uint<LIMBS> {
+    let p_times_16 = Wide::modulus(lis).to_uint::<WIDE_LIMBS>().shl_vartime(4);
+    // 16p is not a square, so its square root is rounded down.
+    let sqrt = p_times_16.sqrt_vartime().wrapping_add(&Uint::ONE);
+    assert!(sqrt.bits() <= Uint::<LIMBS>::BITS, "4 √p does not fit");
+    sqrt.resize()
+}
+
+/// Returns -p<sup>-1</sup> modulo 2<sup>`Limb::BITS`</sup>, as `impl_modulus!` does.
+#[doc(hidden)]
+pub const fn mod_neg_inv<const LIMBS: usize>(modulus: &Uint<LIMBS>) -> Limb {
+    let inverse = modulus.inv_mod2k_vartime(Limb::BITS).expect("p is odd");
+    Limb(inverse.as_limbs()[0].0.wrapping_neg())
+}
+
+/// Returns the leading zeros of p, capped as `impl_modulus!` does.
+#[doc(hidden)]
+pub const fn mod_leading_zeros<const LIMBS: usize>(modulus: &Uint<LIMBS>) -> u32 {
+    let zeros = modulus.leading_zeros();
+    if zeros >= Limb::BITS {
+        Limb::BITS - 1
+    } else {
+        zeros
+    }
+}
+
+/// Returns whether p = 4 ∏ ℓ<sub>i</sub> - 1 passes the Baillie-PSW test, as
+/// [`baillie_psw`](super::primality::baillie_psw) checks it at run time.
+///
+/// As p is 3 modulo 4, the strong test in base 2 passes when 2<sup>(p - 1) / 2</sup> is 1 or -1,
+/// and as p is 3 modulo 8, 2 is not a square and it must be -1. The exponentiation only needs
+/// squarings and doublings. As p + 1 = 2<sup>2</sup> ∏ ℓ<sub>i</sub>, the strong Lucas test starts
+/// from the Lucas sequences of index ∏ ℓ<sub>i</sub>. Everything is computed in Montgomery form.
+#[doc(hidden)]
+pub const fn is_probable_prime(lis: &[u64]) -> bool {
+    let p = Wide::modulus(lis);
+
+    // -1 / p modulo 2^64, with Newton's iteration from the inverse modulo 2^3.
+    let mut inverse = p.words[0];
+    let mut i = 0;
+    while i < 5 {
+        inverse = inverse.wrapping_mul(2u64.wrapping_sub(p.words[0].wrapping_mul(inverse)));
+        i += 1;
+    }
+    let p_inv = inverse.wrapping_neg();
+
+    // 1 in Montgomery form, 2^(64 * len) modulo p.
+    let mut one = Wide {
+        words: [0; WIDE_WORDS],
+        len: p.len,
+    };
+    one.words[0] = 1;
+    let mut i = 0;
+    while i < 64 * p.len {
+        one.double_mod(&p);
+        i += 1;
+    }
+    let zero = Wide {
+        words: [0; WIDE_WORDS],
+        len: p.len,
+    };
+
+    // (p - 1) / 2 = 2 ∏ ℓ - 1 has the bits of p but the lowest one.
+    let mut power = one;
+    let mut bit = p.bits();
+    while bit > 1 {
+        bit -= 1;
+        power = power.monty_mul(&power, &p, p_inv);
+        if p.bit(bit) {
+            power.double_mod(&p);
+        }
+    }
+    if !power.eq(&zero.sub_mod(&one, &p), p.len) {
+        return false;
+    }
+
+    // The first D of 5, -7, 9, -11, ... such that (D / p) = -1.
+    let mut d = 5u32;
+    let mut negative = false;
+    loop {
+        match jacobi_of_discriminant_from_rem(d, negative, p.rem(d as u64) as u32) {
+            -1 => break,
+            // p shares a factor with D, so it is prime only if it is that factor.
+            0 => return p.bits() < u32::BITS && is_prime(p.words[0]),
+            _ => {}
+        }
+        d += 2;
+        negative = !negative;
+    }
+    let mut discriminant = zero;
+    let mut i = 0;
+    while i < d {
+        discriminant = discriminant.add_mod(&one, &p);
+        i += 1;
+    }
+    if negative {
+        discriminant = zero.sub_mod(&discriminant, &p);
+    }
+
+    // The Lucas sequences U and V of P = 1 and Q = (1 - D) / 4. U_odd, V_odd and Q^odd are
+    // computed from the most significant bit of odd = ∏ ℓ, for which they are U_1 = 1, V_1 = P
+    // and Q.
+    let q = one.sub_mod(&discriminant, &p).half_mod(&p).half_mod(&p);
+    let odd = Wide::product(lis);
+    let mut u = one;
+    let mut v = one;
+    let mut q_power = q;
+    let mut bit = odd.bits() - 1;
+    while bit > 0 {
+        bit -= 1;
+        u = u.monty_mul(&v, &p, p_inv);
+        v = v
+            .monty_mul(&v, &p, p_inv)
+            .sub_mod(&q_power, &p)
+            .sub_mod(&q_power, &p);
+        q_power = q_power.monty_mul(&q_power, &p, p_inv);
+        if odd.bit(bit) {
+            (u, v) = (
+                u.add_mod(&v, &p).half_mod(&p),
+                discriminant
+                    .monty_mul(&u, &p, p_inv)
+                    .add_mod(&v, &p)
+                    .half_mod(&p),
+            );
+            q_power = q_power.monty_mul(&q, &p, p_inv);
+        }
+    }
+
+    // p + 1 has 2 trailing zeros, so V is doubled once.
+    u.eq(&zero, p.len)
+        || v.eq(&zero, p.len)
+        || v.monty_mul(&v, &p, p_inv)
+            .sub_mod(&q_power, &p)
+            .sub_mod(&q_power, &p)
+            .eq(&zero, p.len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        CsidhParams,
+        csidh_params::{csidh_512, toy},
+    };
+
+    crate::csidh_params!(mod derived_toy, [3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37]);
+
+    crate::csidh_params!(
+        mod derived_csidh_512,
+        [
+            3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83,
+            89, 97, 101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173, 179,
+            181, 191, 193, 197, 199, 211, 223, 227, 229, 233, 239, 241, 251, 257, 263, 269, 271,
+            277, 281, 283, 293, 307, 311, 313, 317, 331, 337, 347, 349, 353, 359, 367, 373, 587,
+        ],
+        bounds = [5; 74],
+    );
+
+    /// Checks that the derived parameters are the hand-computed ones.
+    macro_rules! check {
+        ($derived:ident, $expected:path, $module:ident) => {{
+            use crypto_bigint::modular::ConstMontyParams;
+
+            let derived = $derived::PARAMS;
+            let expected = $expected;
+            assert_eq!($derived::LIMBS, $module::LIMBS);
+            assert_eq!($derived::N, $module::N);
+            assert_eq!($derived::MOD::MODULUS, $module::MOD::MODULUS);
+            assert_eq!($derived::MOD::ONE, $module::MOD::ONE);
+            assert_eq!($derived::MOD::R2, $module::MOD::R2);
+            assert_eq!($derived::MOD::R3, $module::MOD::R3);
+            assert_eq!($derived::MOD::MOD_NEG_INV, $module::MOD::MOD_NEG_INV);
+            assert_eq!(
+                $derived::MOD::MOD_LEADING_ZEROS,
+                $module::MOD::MOD_LEADING_ZEROS
+            );
+            assert_eq!(derived.lis(), expected.lis());
+            assert_eq!(derived.bounds(), expected.bounds());
+            assert_eq!(derived.p_minus_1_over_2(), expected.p_minus_1_over_2());
+            assert_eq!(
+                derived.inverse_of_4().retrieve(),
+                expected.inverse_of_4().retrieve()
+            );
+            assert_eq!(derived.sqrt_of_p_times_4(), expected.sqrt_of_p_times_4());
+        }};
+    }
+
+    #[test]
+    fn derived_params_match_built_in_params() {
+        check!(derived_toy, toy::TOY, toy);
+        check!(derived_csidh_512, CsidhParams::CSIDH_512, csidh_512);
+        assert_eq!(
+            derived_csidh_512::PARAMS.addition_chains(),
+            CsidhParams::CSIDH_512.addition_chains()
+        );
+    }
+
+    #[test]
+    fn is_probable_prime_matches_is_prime() {
+        // 4 * 3 * 120091 - 1 = 347 * 4153 is a strong pseudoprime in base 2.
+        assert!(!is_probable_prime(&[3, 120091]));
+        for li in (7..2000).step_by(2).filter(|&li| is_prime(li)) {
+            assert_eq!(is_probable_prime(&[3, li]), is_prime(12 * li - 1));
+            assert_eq!(is_probable_prime(&[3, 5, li]), is_prime(60 * li - 1));
+        }
+    }
+}
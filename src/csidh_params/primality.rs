@@ -0,0 +1,240 @@
+//! Primality tests of the prime numbers ℓ<sub>i</sub> and of p.
+
+use crypto_bigint::{BitOps, Integer, Limb, Monty, NonZero, Odd, ShrVartime, WrappingAdd};
+
+/// Bases of the Miller-Rabin test of the ℓ<sub>i</sub>.
+const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Returns whether `n` is prime.
+///
+/// The Miller-Rabin test in the first 12 prime bases is deterministic for all 64-bit integers,
+/// from <i><a href=https://arxiv.org/abs/1509.00864>
+/// Sorenson, J., Webster, J.: Strong pseudoprimes to twelve prime bases. Mathematics of
+/// Computation 86(304). pp. 985–1003 (2017)</a></i>.
+pub(crate) const fn is_prime(n: u64) -> bool {
+    let mut i = 0;
+    while i < BASES.len() {
+        if n == BASES[i] {
+            return true;
+        }
+        if n % BASES[i] == 0 {
+            return false;
+        }
+        i += 1;
+    }
+    if n < 2 {
+        return false;
+    }
+
+    let zeros = (n - 1).trailing_zeros();
+    let odd = (n - 1) >> zeros;
+    let mut i = 0;
+    'bases: while i < BASES.len() {
+        let mut x = pow_mod(BASES[i], odd, n);
+        i += 1;
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        let mut j = 1;
+        while j < zeros {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                continue 'bases;
+            }
+            j += 1;
+        }
+        return false;
+    }
+    true
+}
+
+/// Returns `a` * `b` modulo `n`.
+const fn mul_mod(a: u64, b: u64, n: u64) -> u64 {
+    (a as u128 * b as u128 % n as u128) as u64
+}
+
+/// Returns `base`<sup>`exponent`</sup> modulo `n`.
+const fn pow_mod(base: u64, exponent: u64, n: u64) -> u64 {
+    let mut result = 1;
+    let mut base = base % n;
+    let mut exponent = exponent;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, n);
+        }
+        base = mul_mod(base, base, n);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Returns whether `p`, which must be 3 modulo 4, passes the Baillie-PSW test: it is a strong
+/// probable prime in base 2 and a strong Lucas probable prime, with the parameters of Selfridge,
+/// from <i><a href=https://doi.org/10.1090/S0025-5718-1980-0583518-6>
+/// Baillie, R., Wagstaff, S.S.: Lucas pseudoprimes. Mathematics of Computation 35(152).
+/// pp. 1391–1417 (1980)</a></i>.
+///
+/// No composite number is known to pass it. It is written for any Montgomery representation, so
+/// that parameters of fixed and of dynamic size are checked the same way.
+pub(crate) fn baillie_psw<M: Monty>(p: &Odd<M::Integer>) -> bool {
+    let params = M::new_params_vartime(p.clone());
+    let p = p.as_ref();
+    let one = M::one(params.clone());
+    let zero = M::zero(params.clone());
+
+    // p - 1 = 2 (p - 1) / 2, with (p - 1) / 2 odd.
+    let p_minus_1_over_2 = p.wrapping_shr_vartime(1);
+    let power = one
+        .double()
+        .pow_bounded_exp(&p_minus_1_over_2, p_minus_1_over_2.bits_vartime());
+    if power != one && power != -one.clone() {
+        return false;
+    }
+
+    // The first D of 5, -7, 9, -11, ... such that (D / p) = -1.
+    let mut d = 5u32;
+    let mut negative = false;
+    loop {
+        match jacobi_of_discriminant(d, negative, p) {
+            -1 => break,
+            // p shares a factor with D, so it is prime only if it is that factor.
+            0 => {
+                return p.bits_vartime() < u32::BITS && is_prime(u64::from(p.as_ref()[0].0 as u32));
+            }
+            _ => {}
+        }
+        d += 2;
+        negative = !negative;
+    }
+    let discriminant = M::new(M::Integer::from_limb_like(Limb::from(d), p), params);
+    let discriminant = if negative {
+        -discriminant
+    } else {
+        discriminant
+    };
+
+    // The Lucas sequences U and V of P = 1 and Q = (1 - D) / 4.
+    let q = (one.clone() - &discriminant).div_by_2().div_by_2();
+
+    // p + 1 = 2^zeros * odd. U_odd, V_odd and Q^odd are computed from the most significant bit of
+    // odd, for which they are U_1 = 1, V_1 = P and Q.
+    let p_plus_1 = p.wrapping_add(&M::Integer::one_like(p));
+    let zeros = p_plus_1.trailing_zeros_vartime();
+    let odd = p_plus_1.wrapping_shr_vartime(zeros);
+    let mut u = one.clone();
+    let mut v = one;
+    let mut q_power = q.clone();
+    for bit in (0..odd.bits_vartime() - 1).rev() {
+        u *= &v;
+        v = v.square() - &q_power - &q_power;
+        q_power = q_power.square();
+        if odd.bit_vartime(bit) {
+            (u, v) = (
+                (u.clone() + &v).div_by_2(),
+                (discriminant.clone() * &u + &v).div_by_2(),
+            );
+            q_power *= &q;
+        }
+    }
+
+    if u == zero || v == zero {
+        return true;
+    }
+    for _ in 1..zeros {
+        v = v.square() - &q_power - &q_power;
+        q_power = q_power.square();
+        if v == zero {
+            return true;
+        }
+    }
+    false
+}
+
+/// Returns the Jacobi symbol (D / p) of D = `d`, or -`d` if `negative`, for an odd `d` and p = 3
+/// modulo 4.
+///
+/// By quadratic reciprocity, (d / p) is (p mod d / d), negated if d = 3 modulo 4, and (-1 / p) is
+/// -1, so that only single-word numbers are involved.
+fn jacobi_of_discriminant<T: Integer>(d: u32, negative: bool, p: &T) -> i8 {
+    let p_mod_d = p.rem_limb(NonZero::<Limb>::new_unwrap(Limb::from(d)));
+    jacobi_of_discriminant_from_rem(d, negative, p_mod_d.0 as u32)
+}
+
+/// Returns the Jacobi symbol (D / p) of [`jacobi_of_discriminant`] from p modulo `d`, so that
+/// it can also be computed at compile time.
+pub(crate) const fn jacobi_of_discriminant_from_rem(d: u32, negative: bool, p_mod_d: u32) -> i8 {
+    let symbol = jacobi(p_mod_d as u64, d as u64);
+    if (d % 4 == 3) != negative {
+        -symbol
+    } else {
+        symbol
+    }
+}
+
+/// Returns the Jacobi symbol (`a` / `n`), for an odd `n`.
+const fn jacobi(a: u64, n: u64) -> i8 {
+    let mut a = a % n;
+    let mut n = n;
+    let mut symbol = 1;
+    while a != 0 {
+        while a % 2 == 0 {
+            a /= 2;
+            if n % 8 == 3 || n % 8 == 5 {
+                symbol = -symbol;
+            }
+        }
+        (a, n) = (n, a);
+        if a % 4 == 3 && n % 4 == 3 {
+            symbol = -symbol;
+        }
+        a %= n;
+    }
+    if n == 1 { symbol } else { 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use crypto_bigint::{
+        Uint,
+        modular::{ConstMontyParams, MontyForm},
+    };
+
+    use super::*;
+    use crate::csidh_params::{csidh_512, toy};
+
+    #[test]
+    fn is_prime_matches_trial_division() {
+        for n in 0..10_000u64 {
+            let expected = n >= 2 && (2..n).take_while(|d| d * d <= n).all(|d| n % d != 0);
+            assert_eq!(is_prime(n), expected, "{n}");
+        }
+        // The largest 64-bit prime, and a strong pseudoprime to the first 11 prime bases.
+        assert!(is_prime(18_446_744_073_709_551_557));
+        assert!(!is_prime(3_825_123_056_546_413_051));
+    }
+
+    #[test]
+    fn baillie_psw() {
+        fn passes<const LIMBS: usize>(p: Uint<LIMBS>) -> bool {
+            super::baillie_psw::<MontyForm<LIMBS>>(&Odd::new(p).unwrap())
+        }
+
+        assert!(passes(toy::MOD::MODULUS.get()));
+        assert!(passes(csidh_512::MOD::MODULUS.get()));
+        assert!(passes(Uint::<1>::from(11u32)));
+        assert!(!passes(Uint::<1>::from(4619u32)));
+        // 2047 = 23 * 89 is a strong pseudoprime in base 2.
+        assert!(!passes(Uint::<1>::from(2047u32)));
+    }
+
+    #[test]
+    fn jacobi_matches_euler_criterion() {
+        for n in [3, 7, 11, 19, 23, 31, 43, 47] {
+            for a in 0..2 * n {
+                let euler = pow_mod(a, (n - 1) / 2, n);
+                let expected = if euler == n - 1 { -1 } else { euler as i8 };
+                assert_eq!(jacobi(a, n), expected, "({a} / {n})");
+            }
+        }
+    }
+}
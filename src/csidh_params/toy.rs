@@ -0,0 +1,18 @@
+//! Tiny parameters fitting in a single 64-bit word, used to keep tests fast.
+
+use crypto_bigint::{U64, Uint, impl_modulus, modular::ConstMontyForm};
+
+use super::CsidhParams;
+
+pub const LIMBS: usize = U64::LIMBS;
+
+pub const N: usize = 11;
+
+impl_modulus!(MOD, U64, "00000d7f8cebc633");
+
+pub const TOY: CsidhParams<LIMBS, N, MOD> = CsidhParams::new(
+    [3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37],
+    Uint::from_u64(7420738134809),
+    ConstMontyForm::new(&Uint::from_u64(3710369067405)),
+    Uint::from_u64(15409855),
+);
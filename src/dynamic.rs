@@ -0,0 +1,29 @@
+//! Parameters chosen at runtime, with heap-allocated integers.
+//!
+//! [`DynCsidhParams`], [`DynPrivateKey`] and [`DynPublicKey`] mirror [`CsidhParams`],
+//! [`PrivateKey`] and [`PublicKey`] for parameters that are not known at compile time, such as
+//! primes loaded by research tools. Integers are [`BoxedUint`]s sized after p, and arithmetic
+//! modulo p uses Montgomery parameters computed at runtime.
+//!
+//! The class group action runs through the same code as for parameters of fixed size, in every
+//! [`EvaluationMode`], with the arrays and integers of the evaluation allocated on the heap. The
+//! action maps a curve to a single curve whatever the randomness and the evaluation, so keys and
+//! shared secrets are identical to the ones of the same parameters of fixed size.
+//!
+//! [`CsidhParams`]: crate::CsidhParams
+//! [`EvaluationMode`]: crate::EvaluationMode
+//! [`PrivateKey`]: crate::PrivateKey
+//! [`PublicKey`]: crate::PublicKey
+//! [`BoxedUint`]: crypto_bigint::BoxedUint
+
+mod params;
+mod private_key;
+mod public_key;
+#[cfg(feature = "std")]
+mod search;
+
+pub use params::DynCsidhParams;
+pub use private_key::DynPrivateKey;
+pub use public_key::DynPublicKey;
+#[cfg(feature = "std")]
+pub use search::PrimeSearch;
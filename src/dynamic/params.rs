@@ -0,0 +1,403 @@
+use alloc::{vec, vec::Vec};
+
+use crypto_bigint::{
+    BoxedUint, Limb, Odd,
+    modular::{BoxedMontyForm, BoxedMontyParams, ConstMontyParams},
+};
+use zeroize::DefaultIsZeroes;
+
+use crate::{
+    Error,
+    csidh_params::{
+        CsidhParams, EvaluationMode, Params, SQRT_VELU_THRESHOLD, VALIDATION_ATTEMPTS, check_lis,
+        primality,
+    },
+    field::AdditionChains,
+    strategy,
+};
+
+/// Parameters of the CSIDH key exchange, chosen at runtime.
+///
+/// They follow the rules of [`CsidhParams::new`], and everything but the prime numbers
+/// ℓ<sub>i</sub> is derived from them at construction: p = 4 ∏ ℓ<sub>i</sub> - 1, its Montgomery
+/// parameters, 4√p rounded up and the optimal strategies. Integers modulo p use the smallest
+/// number of limbs that fits p.
+///
+/// Built-in parameters are converted with [`From`]:
+///
+/// ```
+/// use csidh::{CsidhParams, DynCsidhParams, EvaluationMode};
+///
+/// let params = CsidhParams::CSIDH_512.with_evaluation_mode(EvaluationMode::ConstantTime);
+/// let params = DynCsidhParams::from(params);
+/// assert_eq!(params.lis().len(), 74);
+/// assert_eq!(params.p().bits_vartime(), 511);
+/// assert_eq!(params.evaluation_mode(), EvaluationMode::ConstantTime);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DynCsidhParams {
+    lis: Vec<u64>,
+    monty_params: BoxedMontyParams,
+    sqrt_of_p_times_4: BoxedUint,
+    bounds: Vec<u32>,
+    evaluation_mode: EvaluationMode,
+    sqrt_velu_threshold: u64,
+    splits: Vec<u16>,
+    validation_attempts: u32,
+    starting_curve_allowed: bool,
+}
+
+impl DynCsidhParams {
+    /// Constructs the parameters of the prime numbers `lis`, if they respect the rules of
+    /// [`CsidhParams::new`].
+    ///
+    /// The rules are checked in the order of [`CsidhParams::try_new`], the ones on the derived
+    /// values holding by construction: the elements of `lis` are odd prime numbers
+    /// ([`Error::InvalidPrime`]), distinct ([`Error::DuplicatePrime`]), and there is at least one
+    /// ([`Error::MissingPrimes`]), and p passes the Baillie-PSW primality test
+    /// ([`Error::CompositeModulus`]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csidh::{DynCsidhParams, Error};
+    ///
+    /// let params = DynCsidhParams::try_new(vec![3, 5, 7]).unwrap();
+    /// assert_eq!(params.p().as_words(), [419]);
+    ///
+    /// // 4 * 3 * 5 * 7 * 11 - 1 = 4619 = 31 * 149.
+    /// let params = DynCsidhParams::try_new(vec![3, 5, 7, 11]);
+    /// assert_eq!(params, Err(Error::CompositeModulus));
+    /// ```
+    pub fn try_new(lis: Vec<u64>) -> Result<Self, Error> {
+        check_lis(&lis)?;
+
+        let mut product = BoxedUint::from(4u32);
+        for li in &lis {
+            product = product.mul(&BoxedUint::from(*li));
+        }
+        let p = product.wrapping_sub(&BoxedUint::one_with_precision(product.bits_precision()));
+        let p = Odd::new(p.shorten(p.bits_vartime())).unwrap();
+        if !primality::baillie_psw::<BoxedMontyForm>(&p) {
+            return Err(Error::CompositeModulus);
+        }
+
+        // 16p is not a square, so 4√p rounded up is one more than the square root of 16p rounded
+        // down.
+        let precision = p.bits_precision();
+        let p_times_16: BoxedUint = p.widen(precision + Limb::BITS) << 4;
+        let sqrt_of_p_times_4 = p_times_16
+            .sqrt_vartime()
+            .wrapping_add(&BoxedUint::one_with_precision(precision + Limb::BITS))
+            .shorten(precision);
+
+        Ok(Self {
+            bounds: vec![5; lis.len()],
+            splits: optimal_splits(&lis, SQRT_VELU_THRESHOLD),
+            lis,
+            monty_params: BoxedMontyParams::new_vartime(p),
+            sqrt_of_p_times_4,
+            evaluation_mode: EvaluationMode::VariableTime,
+            sqrt_velu_threshold: SQRT_VELU_THRESHOLD,
+            validation_attempts: VALIDATION_ATTEMPTS,
+            starting_curve_allowed: true,
+        })
+    }
+
+    /// Returns the same parameters, with private key elements bounded by `bounds`, as
+    /// [`CsidhParams::with_bounds`] does.
+    ///
+    /// Parameters are constructed with a bound of 5 for every prime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bounds` does not have one element per prime, or if a bound is greater than
+    /// `i32::MAX / 2`.
+    #[must_use]
+    pub fn with_bounds(self, bounds: Vec<u32>) -> Self {
+        assert_eq!(
+            bounds.len(),
+            self.lis.len(),
+            "There must be one bound per prime"
+        );
+        assert!(
+            bounds.iter().all(|&bound| bound <= i32::MAX as u32 / 2),
+            "A doubled bound must fit in an i32"
+        );
+        Self { bounds, ..self }
+    }
+
+    /// Returns the bounds of the private key elements.
+    #[must_use]
+    pub fn bounds(&self) -> &[u32] {
+        &self.bounds
+    }
+
+    /// Returns the bounds of the private key elements in the evaluation mode of the parameters.
+    ///
+    /// They are the [`bounds`](Self::bounds), doubled in [`EvaluationMode::DummyFree`].
+    #[must_use]
+    pub fn key_bounds(&self) -> Vec<u32> {
+        match self.evaluation_mode {
+            EvaluationMode::DummyFree => self.bounds.iter().map(|bound| 2 * bound).collect(),
+            EvaluationMode::VariableTime | EvaluationMode::ConstantTime => self.bounds.clone(),
+        }
+    }
+
+    /// Returns the same parameters, evaluating the class group action with `evaluation_mode`, as
+    /// [`CsidhParams::with_evaluation_mode`] does.
+    #[must_use]
+    pub fn with_evaluation_mode(self, evaluation_mode: EvaluationMode) -> Self {
+        Self {
+            evaluation_mode,
+            ..self
+        }
+    }
+
+    /// Returns the strategy used to evaluate the class group action.
+    #[must_use]
+    pub fn evaluation_mode(&self) -> EvaluationMode {
+        self.evaluation_mode
+    }
+
+    /// Returns the same parameters, computing the isogenies of degree at least `threshold` with
+    /// the square-root Vélu formulas, as [`CsidhParams::with_sqrt_velu_threshold`] does.
+    #[must_use]
+    pub fn with_sqrt_velu_threshold(self, threshold: u64) -> Self {
+        Self {
+            sqrt_velu_threshold: threshold,
+            splits: optimal_splits(&self.lis, threshold),
+            ..self
+        }
+    }
+
+    /// Returns the smallest degree for which isogenies are computed with the square-root Vélu
+    /// formulas.
+    #[must_use]
+    pub fn sqrt_velu_threshold(&self) -> u64 {
+        self.sqrt_velu_threshold
+    }
+
+    /// Returns the same parameters, trying at most `attempts` random points to validate a public
+    /// key, as [`CsidhParams::with_validation_attempts`] does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `attempts` is 0.
+    #[must_use]
+    pub fn with_validation_attempts(self, attempts: u32) -> Self {
+        assert!(attempts > 0, "At least one validation attempt is needed");
+        Self {
+            validation_attempts: attempts,
+            ..self
+        }
+    }
+
+    /// Returns the maximum number of random points tried to validate a public key.
+    #[must_use]
+    pub fn validation_attempts(&self) -> u32 {
+        self.validation_attempts
+    }
+
+    /// Returns the same parameters, accepting the starting curve A = 0 as a public key only if
+    /// `allowed` is true, as [`CsidhParams::with_starting_curve_allowed`] does.
+    #[must_use]
+    pub fn with_starting_curve_allowed(self, allowed: bool) -> Self {
+        Self {
+            starting_curve_allowed: allowed,
+            ..self
+        }
+    }
+
+    /// Returns whether the starting curve A = 0 is accepted as a public key.
+    #[must_use]
+    pub fn starting_curve_allowed(&self) -> bool {
+        self.starting_curve_allowed
+    }
+
+    /// Returns the prime numbers ℓ<sub>i</sub>.
+    #[must_use]
+    pub fn lis(&self) -> &[u64] {
+        &self.lis
+    }
+
+    /// Returns the prime p = 4 ∏ ℓ<sub>i</sub> - 1.
+    #[must_use]
+    pub fn p(&self) -> &BoxedUint {
+        self.monty_params.modulus()
+    }
+
+    pub(crate) fn monty_params(&self) -> &BoxedMontyParams {
+        &self.monty_params
+    }
+
+    pub(crate) fn sqrt_of_p_times_4(&self) -> &BoxedUint {
+        &self.sqrt_of_p_times_4
+    }
+}
+
+/// Computes the splits of the optimal strategies for the primes `lis`.
+fn optimal_splits(lis: &[u64], sqrt_velu_threshold: u64) -> Vec<u16> {
+    let mut splits = vec![0; lis.len()];
+    strategy::optimal_splits(
+        lis,
+        sqrt_velu_threshold,
+        &mut vec![0; lis.len()],
+        &mut splits,
+    );
+    splits
+}
+
+/// Converts parameters of fixed size.
+///
+/// Everything but the addition chains is kept: exponentiations modulo a p chosen at runtime use
+/// square-and-multiply.
+impl<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>>
+    From<CsidhParams<LIMBS, N, MOD>> for DynCsidhParams
+{
+    fn from(params: CsidhParams<LIMBS, N, MOD>) -> Self {
+        Self {
+            lis: Params::lis(&params).to_vec(),
+            monty_params: BoxedMontyParams::from_const_params::<LIMBS, MOD>(),
+            sqrt_of_p_times_4: params.sqrt_of_p_times_4().into(),
+            bounds: params.bounds().to_vec(),
+            evaluation_mode: params.evaluation_mode(),
+            sqrt_velu_threshold: params.sqrt_velu_threshold(),
+            splits: Params::splits(&params).to_vec(),
+            validation_attempts: params.validation_attempts(),
+            starting_curve_allowed: params.starting_curve_allowed(),
+        }
+    }
+}
+
+/// The class group action of parameters chosen at runtime runs through the same code as the one
+/// of parameters of fixed size, with arrays and integers allocated on the heap.
+impl Params for &DynCsidhParams {
+    type Element = BoxedMontyForm;
+    type Integer = BoxedUint;
+    type Array<T> = Vec<T>;
+    type SecretArray<T: DefaultIsZeroes> = Vec<T>;
+
+    fn array<T: Clone>(&self, value: T) -> Vec<T> {
+        vec![value; self.lis.len()]
+    }
+
+    fn secret_array<T: DefaultIsZeroes>(&self) -> Vec<T> {
+        vec![T::default(); self.lis.len()]
+    }
+
+    fn lis(&self) -> &[u64] {
+        &self.lis
+    }
+
+    fn bounds(&self) -> &[u32] {
+        &self.bounds
+    }
+
+    fn evaluation_mode(&self) -> EvaluationMode {
+        self.evaluation_mode
+    }
+
+    fn sqrt_velu_threshold(&self) -> u64 {
+        self.sqrt_velu_threshold
+    }
+
+    fn validation_attempts(&self) -> u32 {
+        self.validation_attempts
+    }
+
+    fn splits(&self) -> &[u16] {
+        &self.splits
+    }
+
+    fn addition_chains(&self) -> Option<AdditionChains> {
+        None
+    }
+
+    fn p(&self) -> BoxedUint {
+        self.monty_params.modulus().as_ref().clone()
+    }
+
+    fn sqrt_of_p_times_4(&self) -> &BoxedUint {
+        &self.sqrt_of_p_times_4
+    }
+
+    fn inverse_of_4(&self) -> BoxedMontyForm {
+        // p is 3 modulo 4, so (p + 1) / 4 is the inverse of 4.
+        let p = self.monty_params.modulus();
+        let one = BoxedUint::one_with_precision(p.bits_precision());
+        BoxedMontyForm::new(
+            p.wrapping_shr_vartime(2).wrapping_add(&one),
+            self.monty_params.clone(),
+        )
+    }
+
+    fn integer(&self, x: u64) -> BoxedUint {
+        let precision = self.monty_params.bits_precision();
+        let x = BoxedUint::from(x);
+        if x.bits_precision() < precision {
+            x.widen(precision)
+        } else {
+            x.shorten(precision)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csidh_params::toy;
+
+    #[test]
+    fn derived_values_match_built_in_params() {
+        assert_eq!(
+            DynCsidhParams::try_new(toy::TOY.lis().to_vec()),
+            Ok(DynCsidhParams::from(toy::TOY))
+        );
+        assert_eq!(
+            DynCsidhParams::try_new(CsidhParams::CSIDH_512.lis().to_vec()),
+            Ok(DynCsidhParams::from(CsidhParams::CSIDH_512))
+        );
+        assert_eq!(
+            DynCsidhParams::try_new(CsidhParams::CSIDH_1024.lis().to_vec()),
+            Ok(DynCsidhParams::from(CsidhParams::CSIDH_1024))
+        );
+        assert_eq!(
+            DynCsidhParams::try_new(CsidhParams::CSIDH_1792.lis().to_vec()),
+            Ok(DynCsidhParams::from(CsidhParams::CSIDH_1792))
+        );
+    }
+
+    #[test]
+    fn conversion_keeps_the_settings() {
+        let params = toy::TOY
+            .with_evaluation_mode(EvaluationMode::DummyFree)
+            .with_sqrt_velu_threshold(5);
+        let dyn_params = DynCsidhParams::from(params);
+        assert_eq!(
+            dyn_params,
+            DynCsidhParams::try_new(toy::TOY.lis().to_vec())
+                .unwrap()
+                .with_evaluation_mode(EvaluationMode::DummyFree)
+                .with_sqrt_velu_threshold(5)
+        );
+        assert_eq!(dyn_params.key_bounds(), params.key_bounds());
+    }
+
+    #[test]
+    fn try_new_names_the_violated_rule() {
+        assert_eq!(
+            DynCsidhParams::try_new(vec![3, 5, 9]),
+            Err(Error::InvalidPrime)
+        );
+        assert_eq!(
+            DynCsidhParams::try_new(vec![3, 5, 5]),
+            Err(Error::DuplicatePrime)
+        );
+        assert_eq!(DynCsidhParams::try_new(vec![]), Err(Error::MissingPrimes));
+        assert_eq!(
+            DynCsidhParams::try_new(vec![3, 5, 7, 11]),
+            Err(Error::CompositeModulus)
+        );
+    }
+}
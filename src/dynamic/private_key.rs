@@ -0,0 +1,180 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::{convert::Infallible, fmt};
+
+use crypto_bigint::{modular::BoxedMontyForm, rand_core::CryptoRngCore};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use super::{DynCsidhParams, DynPublicKey};
+use crate::{Error, EvaluationMode, csidh::csidh, private_key::uniform_below};
+
+/// A private key for the CSIDH key exchange with [`DynCsidhParams`].
+///
+/// The key is wiped from memory when dropped, and its [`Debug`](fmt::Debug) output does not
+/// reveal it.
+#[derive(Clone)]
+pub struct DynPrivateKey {
+    params: DynCsidhParams,
+    key: Vec<i32>,
+}
+
+impl DynPrivateKey {
+    /// Constructs a new `DynPrivateKey` from the given `key`, if it is valid for `params`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidLength`] if `key` does not have one element per prime of `params`,
+    /// [`Error::ExponentOutOfBounds`] if a key element is not between minus and plus its bound,
+    /// doubled in [`EvaluationMode::DummyFree`], and [`Error::ExponentParity`] if it is odd in
+    /// this mode.
+    pub fn try_new(params: DynCsidhParams, key: Vec<i32>) -> Result<Self, Error> {
+        let private_key = Self { params, key };
+        if private_key.key.len() != private_key.params.lis().len() {
+            return Err(Error::InvalidLength);
+        }
+        let dummy_free = private_key.params.evaluation_mode() == EvaluationMode::DummyFree;
+        let bounds = private_key.params.key_bounds();
+        for (e, bound) in private_key.key.iter().zip(bounds) {
+            if e.unsigned_abs() > bound {
+                return Err(Error::ExponentOutOfBounds);
+            }
+            if dummy_free && e % 2 != 0 {
+                return Err(Error::ExponentParity);
+            }
+        }
+        Ok(private_key)
+    }
+
+    /// Generates a random `DynPrivateKey`.
+    ///
+    /// Each key element is sampled uniformly and independently between minus and plus its bound,
+    /// or among the even numbers between minus and plus twice its bound if `params` use
+    /// [`EvaluationMode::DummyFree`].
+    #[must_use]
+    pub fn random(params: DynCsidhParams, rng: &mut impl CryptoRngCore) -> Self {
+        let mut next = || Ok::<_, Infallible>(rng.next_u32());
+        let dummy_free = params.evaluation_mode() == EvaluationMode::DummyFree;
+        let key = params
+            .bounds()
+            .iter()
+            .map(|&bound| {
+                let Ok(sample) = uniform_below(2 * bound + 1, &mut next);
+                if dummy_free {
+                    (2 * sample).wrapping_sub(2 * bound) as i32
+                } else {
+                    sample.wrapping_sub(bound) as i32
+                }
+            })
+            .collect();
+        Self { params, key }
+    }
+
+    /// Computes the shared secret of the key exchange with a foreign public key.
+    ///
+    /// The shared secret is returned as the encoding of [`DynPublicKey::to_bytes`] of the shared
+    /// curve, which is the input keying material of
+    /// [`SharedSecret::derive_key`](crate::SharedSecret::derive_key) for parameters of fixed
+    /// size.
+    #[must_use]
+    pub fn shared_secret(
+        &self,
+        foreign_public_key: &DynPublicKey,
+        rng: &mut impl CryptoRngCore,
+    ) -> Zeroizing<Box<[u8]>> {
+        let mut shared_secret = self.act(foreign_public_key.coefficient(), rng);
+        let bytes = Zeroizing::new(shared_secret.retrieve().to_le_bytes());
+        shared_secret.zeroize();
+        bytes
+    }
+
+    /// Returns the coefficient of the curve reached by the action of the key on the curve of
+    /// coefficient `start`.
+    pub(crate) fn act(
+        &self,
+        start: &BoxedMontyForm,
+        rng: &mut impl CryptoRngCore,
+    ) -> BoxedMontyForm {
+        csidh(&self.params, &self.key, start.clone(), rng)
+    }
+
+    pub(crate) fn params(&self) -> &DynCsidhParams {
+        &self.params
+    }
+}
+
+impl fmt::Debug for DynPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynPrivateKey")
+            .field("params", &self.params)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Zeroize for DynPrivateKey {
+    fn zeroize(&mut self) {
+        self.key.zeroize();
+    }
+}
+
+impl Drop for DynPrivateKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for DynPrivateKey {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::csidh_params::toy;
+
+    #[test]
+    fn try_new_reports_invalid_keys() {
+        let params = DynCsidhParams::from(toy::TOY);
+        assert!(DynPrivateKey::try_new(params.clone(), vec![5; toy::N]).is_ok());
+        assert_eq!(
+            DynPrivateKey::try_new(params.clone(), vec![0; toy::N - 1]).err(),
+            Some(Error::InvalidLength)
+        );
+        let mut key = vec![0; toy::N];
+        key[3] = -6;
+        assert_eq!(
+            DynPrivateKey::try_new(params, key).err(),
+            Some(Error::ExponentOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn random_key_is_in_bounds() {
+        let mut rng = rand::thread_rng();
+        let params = DynCsidhParams::from(toy::TOY).with_bounds(vec![2; toy::N]);
+        for _ in 0..10 {
+            let private_key = DynPrivateKey::random(params.clone(), &mut rng);
+            assert!(private_key.key.iter().all(|&e| (-2..=2).contains(&e)));
+        }
+    }
+
+    #[test]
+    fn dummy_free_keys_are_even() {
+        let mut rng = rand::thread_rng();
+        let params = DynCsidhParams::from(toy::TOY.with_evaluation_mode(EvaluationMode::DummyFree));
+        let private_key = DynPrivateKey::random(params.clone(), &mut rng);
+        assert!(
+            private_key
+                .key
+                .iter()
+                .all(|&e| e % 2 == 0 && (-10..=10).contains(&e))
+        );
+
+        let mut key = vec![0; toy::N];
+        key[0] = 10;
+        assert!(DynPrivateKey::try_new(params.clone(), key.clone()).is_ok());
+        key[0] = 3;
+        assert_eq!(
+            DynPrivateKey::try_new(params, key).err(),
+            Some(Error::ExponentParity)
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use alloc::boxed::Box;
+
+use crypto_bigint::{BoxedUint, modular::BoxedMontyForm, rand_core::CryptoRngCore};
+
+use super::{DynCsidhParams, DynPrivateKey};
+use crate::{Error, montgomery_curve::MontgomeryCurve};
+
+/// A public key for the CSIDH key exchange with [`DynCsidhParams`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DynPublicKey {
+    key: BoxedMontyForm,
+}
+
+impl DynPublicKey {
+    /// Computes the public key associated with the given private key.
+    #[must_use]
+    pub fn from(private_key: &DynPrivateKey, rng: &mut impl CryptoRngCore) -> Self {
+        let start = BoxedMontyForm::zero(private_key.params().monty_params().clone());
+        Self {
+            key: private_key.act(&start, rng),
+        }
+    }
+
+    /// Constructs a `DynPublicKey` from the foreign public key, if the key is valid.
+    ///
+    /// The key is the Montgomery coefficient A of a curve, checked as in
+    /// [`PublicKey::try_new_with_certificate`](crate::PublicKey::try_new_with_certificate).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NonCanonicalEncoding`], [`Error::SingularCurve`],
+    /// [`Error::StartingCurve`] or [`Error::OrdinaryCurve`] for the first check the key fails.
+    pub fn try_new(
+        params: &DynCsidhParams,
+        key: &BoxedUint,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<Self, Error> {
+        let precision = params.p().bits_precision();
+        let key = if key.bits_vartime() > precision {
+            return Err(Error::NonCanonicalEncoding);
+        } else if key.bits_precision() < precision {
+            key.widen(precision)
+        } else {
+            key.shorten(precision)
+        };
+        Self::validate(params, key, rng)
+    }
+
+    /// Encodes the public key as the little-endian bytes of the Montgomery coefficient A of its
+    /// curve.
+    ///
+    /// The encoding is canonical, and has as many bytes as the limbs of p, like the one of
+    /// [`PublicKey::to_bytes`](crate::PublicKey::to_bytes) for the same p and smallest number of
+    /// limbs.
+    #[must_use]
+    pub fn to_bytes(&self) -> Box<[u8]> {
+        self.key.retrieve().to_le_bytes()
+    }
+
+    /// Decodes a foreign public key encoded with [`DynPublicKey::to_bytes`], if the key is valid.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidLength`] if the encoding does not have the expected length, and
+    /// the errors of [`DynPublicKey::try_new`] otherwise.
+    pub fn from_bytes(
+        params: &DynCsidhParams,
+        bytes: &[u8],
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<Self, Error> {
+        let precision = params.p().bits_precision();
+        if bytes.len() * 8 != precision as usize {
+            return Err(Error::InvalidLength);
+        }
+        let key = BoxedUint::from_le_slice(bytes, precision).map_err(|_| Error::InvalidLength)?;
+        Self::validate(params, key, rng)
+    }
+
+    /// Returns the Montgomery coefficient A of the curve of the key.
+    #[must_use]
+    pub fn key(&self) -> BoxedUint {
+        self.key.retrieve()
+    }
+
+    pub(crate) fn coefficient(&self) -> &BoxedMontyForm {
+        &self.key
+    }
+
+    /// Checks `key`, of the precision of p.
+    fn validate(
+        params: &DynCsidhParams,
+        key: BoxedUint,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<Self, Error> {
+        let p = params.p();
+        if key >= *p {
+            return Err(Error::NonCanonicalEncoding);
+        }
+        let two = BoxedUint::from(2u32).widen(p.bits_precision());
+        if key == two || key == p.wrapping_sub(&two) {
+            return Err(Error::SingularCurve);
+        }
+        if bool::from(key.is_zero()) && !params.starting_curve_allowed() {
+            return Err(Error::StartingCurve);
+        }
+
+        let key = BoxedMontyForm::new(key, params.monty_params().clone());
+        if MontgomeryCurve::new(params, key.clone()).is_supersingular(rng) {
+            Ok(Self { key })
+        } else {
+            Err(Error::OrdinaryCurve)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CsidhParams, EvaluationMode, KeyPair, PrivateKey, PublicKey, csidh_params::toy};
+
+    #[test]
+    fn keys_match_fixed_size_keys() {
+        let mut rng = rand::thread_rng();
+        for mode in [
+            EvaluationMode::VariableTime,
+            EvaluationMode::ConstantTime,
+            EvaluationMode::DummyFree,
+        ] {
+            let params = toy::TOY.with_evaluation_mode(mode);
+            let key_pair = KeyPair::generate(params, &mut rng);
+            let private_key = DynPrivateKey::try_new(
+                DynCsidhParams::from(params),
+                key_pair.private_key().key().to_vec(),
+            )
+            .unwrap();
+            let public_key = DynPublicKey::from(&private_key, &mut rng);
+            assert_eq!(*public_key.to_bytes(), key_pair.public_key().to_bytes());
+        }
+    }
+
+    #[test]
+    fn keys_match_csidh_512_keys() {
+        let mut rng = rand::thread_rng();
+        let params = CsidhParams::CSIDH_512.with_bounds([1; 74]);
+        let private_key = PrivateKey::random(params, &mut rng);
+        let public_key = PublicKey::from(&private_key, &mut rng);
+        let dyn_private_key =
+            DynPrivateKey::try_new(DynCsidhParams::from(params), private_key.key().to_vec())
+                .unwrap();
+        let dyn_public_key = DynPublicKey::from(&dyn_private_key, &mut rng);
+        assert_eq!(*dyn_public_key.to_bytes(), public_key.to_bytes());
+    }
+
+    #[test]
+    fn shared_secrets_match() {
+        let mut rng = rand::thread_rng();
+        let params = DynCsidhParams::from(toy::TOY);
+        let alice = DynPrivateKey::random(params.clone(), &mut rng);
+        let bob = DynPrivateKey::random(params.clone(), &mut rng);
+        let alice_public_key = DynPublicKey::from(&alice, &mut rng);
+        let bob_public_key = DynPublicKey::from(&bob, &mut rng);
+        let shared_secret = alice.shared_secret(&bob_public_key, &mut rng);
+        assert_eq!(
+            shared_secret,
+            bob.shared_secret(&alice_public_key, &mut rng)
+        );
+
+        let bob_public_key =
+            DynPublicKey::from_bytes(&params, &bob_public_key.to_bytes(), &mut rng).unwrap();
+        assert_eq!(
+            alice.shared_secret(&bob_public_key, &mut rng),
+            shared_secret
+        );
+    }
+
+    #[test]
+    fn try_new_reports_invalid_curves() {
+        let mut rng = rand::thread_rng();
+        let params = DynCsidhParams::from(toy::TOY);
+        let p = params.p().clone();
+        let key = |x: u32| BoxedUint::from(x);
+        assert!(DynPublicKey::try_new(&params, &key(0), &mut rng).is_ok());
+        assert_eq!(
+            DynPublicKey::try_new(&params, &p, &mut rng),
+            Err(Error::NonCanonicalEncoding)
+        );
+        assert_eq!(
+            DynPublicKey::try_new(&params, &key(2), &mut rng),
+            Err(Error::SingularCurve)
+        );
+        assert_eq!(
+            DynPublicKey::try_new(
+                &params.clone().with_starting_curve_allowed(false),
+                &key(0),
+                &mut rng
+            ),
+            Err(Error::StartingCurve)
+        );
+        assert_eq!(
+            DynPublicKey::try_new(&params, &key(3), &mut rng),
+            Err(Error::OrdinaryCurve)
+        );
+        assert_eq!(
+            DynPublicKey::from_bytes(&params, &[0; 3], &mut rng),
+            Err(Error::InvalidLength)
+        );
+    }
+}
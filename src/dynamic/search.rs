@@ -0,0 +1,371 @@
+//! Search for new parameters, and generation of their source code.
+
+use std::{
+    format, iter,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crypto_bigint::BoxedUint;
+
+use super::DynCsidhParams;
+use crate::{Error, csidh_params::primality::is_prime};
+
+/// Number of hexadecimal digits per line of the generated source.
+const HEX_DIGITS_PER_LINE: usize = 80;
+
+/// Numbers of 64-bit words of the integers `impl_modulus!` defines a modulus for, which must
+/// concatenate with themselves.
+const MODULUS_WORDS: [u32; 20] = [
+    1, 2, 3, 4, 5, 6, 7, 8, 10, 12, 14, 16, 24, 28, 32, 33, 34, 48, 64, 128,
+];
+
+/// Search for primes p = 4 ∏ ℓ<sub>i</sub> - 1 of at most a given number of bits.
+///
+/// The ℓ<sub>i</sub> are the smallest odd primes, as many as fit, along with a last prime which
+/// is searched for so that p is prime. This is how CSIDH-512 was built, with 587 as its last
+/// prime. Primes can be dropped from the smallest ones with
+/// [`with_dropped_primes`](Self::with_dropped_primes), and others added with
+/// [`with_added_primes`](Self::with_added_primes), which together replace primes: CSIDH-1024 is
+/// found by adding 863, and CSIDH-1792 by dropping the primes from 3 to 31 and 1297, its last
+/// prime being 1657.
+///
+/// # Example
+///
+/// ```
+/// use csidh::PrimeSearch;
+///
+/// let params = PrimeSearch::new(512).results().next().unwrap();
+/// assert_eq!(params.lis().len(), 74);
+/// assert_eq!(params.lis()[73], 587);
+/// assert_eq!(params.p().bits_vartime(), 511);
+///
+/// // The source of a `csidh_params` submodule defining `CsidhParams::CSIDH_512`.
+/// let source = params.to_module_source("512");
+/// assert!(source.contains("pub const CSIDH_512"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrimeSearch {
+    bits: u32,
+    dropped: Vec<u64>,
+    added: Vec<u64>,
+}
+
+impl PrimeSearch {
+    /// Constructs a search for primes p of at most `bits` bits.
+    #[must_use]
+    pub fn new(bits: u32) -> Self {
+        Self {
+            bits,
+            dropped: Vec::new(),
+            added: Vec::new(),
+        }
+    }
+
+    /// Returns the same search, without the odd primes `primes` among the ℓ<sub>i</sub>.
+    #[must_use]
+    pub fn with_dropped_primes(self, primes: Vec<u64>) -> Self {
+        Self {
+            dropped: primes,
+            ..self
+        }
+    }
+
+    /// Returns the same search, with the odd primes `primes` among the ℓ<sub>i</sub> of every
+    /// result.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an element of `primes` is not an odd prime number, or appears twice.
+    #[must_use]
+    pub fn with_added_primes(self, primes: Vec<u64>) -> Self {
+        assert!(
+            primes.iter().all(|&li| li != 2 && is_prime(li)),
+            "The added primes must be odd prime numbers"
+        );
+        assert!(
+            (primes.iter().enumerate()).all(|(i, li)| !primes[..i].contains(li)),
+            "The added primes must be distinct"
+        );
+        Self {
+            added: primes,
+            ..self
+        }
+    }
+
+    /// Returns the parameters found, from the ones with the most primes ℓ<sub>i</sub>.
+    ///
+    /// For each number of smallest odd primes, from the largest one that fits, the last prime is
+    /// searched for in increasing order among the larger ones, as long as p has at most the
+    /// requested number of bits. The iterator is lazy, and each result costs a few primality
+    /// tests of p.
+    pub fn results(&self) -> impl Iterator<Item = DynCsidhParams> + '_ {
+        let is_candidate = |li: &u64| !self.dropped.contains(li) && !self.added.contains(li);
+        let candidates = move |first: u64| {
+            (first..)
+                .step_by(2)
+                .filter(|&li| is_prime(li))
+                .filter(is_candidate)
+        };
+        // The smallest primes whose product fits, followed by the first one that does not.
+        let mut smallest = Vec::new();
+        for li in candidates(3) {
+            smallest.push(li);
+            if self.product_bits(&smallest) > self.bits {
+                break;
+            }
+        }
+
+        // At least one of the smallest primes is taken, along with the last prime.
+        (1..smallest.len().saturating_sub(1))
+            .rev()
+            .flat_map(move |count| {
+                let base = smallest[..count].to_vec();
+                candidates(smallest[count]).map_while(move |li| {
+                    let mut lis = [&base, &[li][..]].concat();
+                    (self.product_bits(&lis) <= self.bits).then(|| {
+                        lis.extend_from_slice(&self.added);
+                        lis.sort_unstable();
+                        lis
+                    })
+                })
+            })
+            .filter_map(|lis| match DynCsidhParams::try_new(lis) {
+                Ok(params) => Some(params),
+                Err(Error::CompositeModulus) => None,
+                Err(error) => unreachable!("The primes are valid: {error}"),
+            })
+    }
+
+    /// Returns the number of bits of 4 times the product of `lis` and of the added primes, which
+    /// is also the one of p.
+    fn product_bits(&self, lis: &[u64]) -> u32 {
+        (lis.iter().chain(&self.added))
+            .fold(BoxedUint::from(4u32), |product, li| {
+                product.mul(&BoxedUint::from(*li))
+            })
+            .bits_vartime()
+    }
+}
+
+impl DynCsidhParams {
+    /// Returns the source of a `csidh_params` submodule defining these parameters as
+    /// `CsidhParams::CSIDH_<name>`, with their derived constants and a test checking them.
+    ///
+    /// The submodule follows the ones of the built-in parameters: it is meant to be saved as
+    /// `src/csidh_params/csidh_<name>.rs` and declared in `src/csidh_params.rs`. Integers use the
+    /// smallest number of limbs that fits p and that `impl_modulus!` accepts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if p has more than 8192 bits.
+    #[must_use]
+    pub fn to_module_source(&self, name: &str) -> String {
+        let p = self.p();
+        let words = *MODULUS_WORDS
+            .iter()
+            .find(|&&words| words * 64 >= p.bits_vartime())
+            .expect("p must have at most 8192 bits");
+        let product = p.wrapping_add(&BoxedUint::one_with_precision(p.bits_precision())) >> 2;
+        let p_minus_1_over_2 = p.clone() >> 1;
+        let upper_name = name.to_uppercase();
+
+        format!(
+            r#"use crypto_bigint::{{Uint, impl_modulus, modular::ConstMontyForm}};
+
+use super::CsidhParams;
+
+#[cfg(target_pointer_width = "32")]
+pub const LIMBS: usize = {limbs_32};
+#[cfg(target_pointer_width = "64")]
+/// Number of limbs used to store public keys and shared secrets in CSIDH-{name}
+pub const LIMBS: usize = {words};
+
+/// Number of prime numbers used for CSIDH-{name}
+pub const N: usize = {n};
+
+impl_modulus!(
+    MOD,
+    Uint<LIMBS>,
+    {p}
+);
+
+/// (p - 1) / 2
+const P_MINUS_1_OVER_2: Uint<LIMBS> = Uint::from_be_hex(
+    {p_minus_1_over_2},
+);
+
+impl CsidhParams<LIMBS, N, MOD> {{
+    /// A proposition for CSIDH-{name}.
+    pub const CSIDH_{upper_name}: CsidhParams<LIMBS, N, MOD> = CsidhParams::new(
+        [
+{lis},
+        ],
+        P_MINUS_1_OVER_2,
+        ConstMontyForm::new(&Uint::from_be_hex(
+            {product},
+        )),
+        Uint::from_be_hex(
+            {sqrt_of_p_times_4},
+        ),
+    )
+    .with_addition_chains(addition_chains!(LIMBS, P_MINUS_1_OVER_2));
+}}
+
+#[cfg(test)]
+mod tests {{
+    use super::*;
+
+    #[test]
+    fn constants_are_derived_from_the_primes() {{
+        let params = CsidhParams::CSIDH_{upper_name};
+        assert!(
+            CsidhParams::try_new(
+                params.lis(),
+                params.p_minus_1_over_2(),
+                params.inverse_of_4(),
+                params.sqrt_of_p_times_4(),
+            )
+            .is_ok()
+        );
+    }}
+}}
+"#,
+            limbs_32 = 2 * words,
+            n = self.lis().len(),
+            p = hex(p, words, "    "),
+            p_minus_1_over_2 = hex(&p_minus_1_over_2, words, "    "),
+            lis = wrap(self.lis(), "            "),
+            product = hex(&product, words, "            "),
+            sqrt_of_p_times_4 = hex(self.sqrt_of_p_times_4(), words, "            "),
+        )
+    }
+}
+
+impl DynCsidhParams {
+    /// Returns an invocation of the [`csidh_params!`](crate::csidh_params) macro defining these
+    /// parameters in a module `csidh_<name>`, which derives the constants of
+    /// [`to_module_source`](Self::to_module_source) at compile time.
+    ///
+    /// Like the source of a submodule, the invocation leaves the bounds of the private key
+    /// elements to their default.
+    #[must_use]
+    pub fn to_macro_source(&self, name: &str) -> String {
+        format!(
+            r#"csidh::csidh_params!(
+    /// A proposition for CSIDH-{name}.
+    pub mod csidh_{name},
+    [
+{lis},
+    ]
+);
+"#,
+            lis = wrap(self.lis(), "        "),
+        )
+    }
+}
+
+/// Returns the string literal of the big-endian hexadecimal digits of `value` on `words` 64-bit
+/// words, split over lines starting with `indent`.
+fn hex(value: &BoxedUint, words: u32, indent: &str) -> String {
+    let bytes = value.to_be_bytes();
+    let padding = words as usize * 8 - bytes.len();
+    let digits: String = (iter::repeat_n(0, padding).chain(bytes.iter().copied()))
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    let lines: Vec<&str> = (digits.as_bytes().chunks(HEX_DIGITS_PER_LINE))
+        .map(|chunk| core::str::from_utf8(chunk).unwrap())
+        .collect();
+    format!("\"{}\"", lines.join(&format!("\\\n{indent}")))
+}
+
+/// Returns the elements of `lis` separated by commas, over lines of less than 100 characters
+/// starting with `indent`, as rustfmt lays them out.
+fn wrap(lis: &[u64], indent: &str) -> String {
+    let mut lines = Vec::new();
+    let mut line = String::from(indent);
+    for li in lis {
+        let li = li.to_string();
+        if line.len() > indent.len() && line.len() + li.len() + 1 >= 100 {
+            lines.push(line.trim_end().to_string());
+            line = String::from(indent);
+        }
+        line.push_str(&li);
+        line.push_str(", ");
+    }
+    lines.push(line.trim_end().trim_end_matches(',').to_string());
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec;
+
+    use super::*;
+    use crate::CsidhParams;
+
+    #[test]
+    fn search_finds_csidh_512() {
+        let params = PrimeSearch::new(512).results().next().unwrap();
+        assert_eq!(params, DynCsidhParams::from(CsidhParams::CSIDH_512));
+    }
+
+    #[test]
+    fn search_finds_csidh_1024() {
+        let search = PrimeSearch::new(1024).with_added_primes(vec![863]);
+        let params = search.results().next().unwrap();
+        assert_eq!(params, DynCsidhParams::from(CsidhParams::CSIDH_1024));
+    }
+
+    #[test]
+    fn search_finds_csidh_1792() {
+        let search = PrimeSearch::new(1792)
+            .with_dropped_primes(vec![3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 1297]);
+        let params = search.results().next().unwrap();
+        assert_eq!(params, DynCsidhParams::from(CsidhParams::CSIDH_1792));
+    }
+
+    #[test]
+    fn search_drops_and_adds_primes() {
+        let search = PrimeSearch::new(64)
+            .with_dropped_primes(vec![5])
+            .with_added_primes(vec![101]);
+        let results: Vec<_> = search.results().take(3).collect();
+        assert_eq!(results.len(), 3);
+        for params in results {
+            assert!(!params.lis().contains(&5));
+            assert!(params.lis().contains(&101));
+            assert!(params.p().bits_vartime() <= 64);
+        }
+    }
+
+    #[test]
+    fn module_source_holds_the_built_in_constants() {
+        let source = DynCsidhParams::from(CsidhParams::CSIDH_512).to_module_source("512");
+        let source: String = source.split("\\\n").map(str::trim_start).collect();
+        // p, (p - 1) / 2, the product of the primes and 4√p rounded up, as in csidh_512.rs.
+        for constant in [
+            "65b48e8f740f89bffc8ab0d15e3e4c4ab42d083aedc88c425afbfcc69322c9c\
+            da7aac6c567f35507516730cc1f0b4f25c2721bf457aca8351b81b90533c6c87b",
+            "32da4747ba07c4dffe455868af1f26255a16841d76e446212d7dfe63499164e\
+            6d3d56362b3f9aa83a8b398660f85a792e1390dfa2bd6541a8dc0dc8299e3643d",
+            "196d23a3dd03e26fff22ac34578f9312ad0b420ebb72231096beff31a4c8b27\
+            369eab1b159fcd541d459cc3307c2d3c9709c86fd15eb2a0d46e06e414cf1b21f",
+            "000000000000000000000000000000000000000000000000000000000000000\
+            2856f1399d91d6592142b9541e59682cd38d0cd95f8636a5617895e71e1a20b40",
+        ] {
+            assert!(source.contains(constant));
+        }
+        assert!(source.contains("pub const N: usize = 74;"));
+        assert!(source.contains("pub const CSIDH_512: CsidhParams<LIMBS, N, MOD>"));
+        assert!(source.contains("373, 587,\n        ],"));
+    }
+
+    #[test]
+    fn macro_source_lists_the_primes() {
+        let source = DynCsidhParams::from(CsidhParams::CSIDH_512).to_macro_source("512");
+        assert!(source.starts_with("csidh::csidh_params!(\n"));
+        assert!(source.contains("    pub mod csidh_512,\n    [\n        3, 5, 7, 11,"));
+        assert!(source.ends_with("373, 587,\n    ]\n);\n"));
+    }
+}
@@ -0,0 +1,77 @@
+use core::fmt;
+
+/// Errors returned by the fallible constructors of the crate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// An encoding does not have the expected length.
+    InvalidLength,
+    /// An encoding is not the canonical one of any value: an integer is not reduced, unused bits
+    /// are set, or the format is unknown.
+    NonCanonicalEncoding,
+    /// A public key has a Montgomery coefficient A of 2 or -2, which describes a singular curve.
+    SingularCurve,
+    /// A public key is the starting curve A = 0, which the [`CsidhParams`](crate::CsidhParams)
+    /// reject.
+    StartingCurve,
+    /// A public key describes a curve that could not be proven supersingular within
+    /// [`CsidhParams::validation_attempts`](crate::CsidhParams::validation_attempts) attempts.
+    /// Apart from a negligible probability, the curve is ordinary.
+    OrdinaryCurve,
+    /// A private key element is not between minus and plus its bound.
+    ExponentOutOfBounds,
+    /// A private key element is odd, while the parameters use
+    /// [`EvaluationMode::DummyFree`](crate::EvaluationMode::DummyFree).
+    ExponentParity,
+    /// An encoded private key belongs to other parameters, or its format cannot represent the
+    /// bounds of the parameters.
+    ParameterMismatch,
+    /// The random number generator failed.
+    RngFailure,
+    /// A prime number ℓ<sub>i</sub> of the parameters is not an odd prime number.
+    InvalidPrime,
+    /// The prime numbers ℓ<sub>i</sub> of the parameters are not distinct.
+    DuplicatePrime,
+    /// The parameters have no prime number ℓ<sub>i</sub>.
+    MissingPrimes,
+    /// The modulus of the parameters is not 4 ∏ ℓ<sub>i</sub> - 1, or does not fit in their
+    /// number of limbs.
+    ModulusMismatch,
+    /// The modulus p of the parameters is not prime.
+    CompositeModulus,
+    /// `p_minus_1_over_2` is not (p - 1) / 2.
+    InvalidPMinus1Over2,
+    /// `inverse_of_4` is not the inverse of 4 modulo p.
+    InvalidInverseOf4,
+    /// `sqrt_of_p_times_4` is not 4 √p rounded up.
+    InvalidSqrtOfPTimes4,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::InvalidLength => "invalid encoding length",
+            Self::NonCanonicalEncoding => "non-canonical encoding",
+            Self::SingularCurve => "singular curve",
+            Self::StartingCurve => "starting curve",
+            Self::OrdinaryCurve => "ordinary curve",
+            Self::ExponentOutOfBounds => "private key element out of bounds",
+            Self::ExponentParity => "private key element of the wrong parity",
+            Self::ParameterMismatch => "parameter mismatch",
+            Self::RngFailure => "random number generator failure",
+            Self::InvalidPrime => "a prime of the parameters is not an odd prime number",
+            Self::DuplicatePrime => "the primes of the parameters are not distinct",
+            Self::MissingPrimes => "the parameters have no primes",
+            Self::ModulusMismatch => {
+                "the modulus is not 4 times the product of the primes, minus 1"
+            }
+            Self::CompositeModulus => "the modulus is not prime",
+            Self::InvalidPMinus1Over2 => "p_minus_1_over_2 is not (p - 1) / 2",
+            Self::InvalidInverseOf4 => "inverse_of_4 is not the inverse of 4 modulo p",
+            Self::InvalidSqrtOfPTimes4 => "sqrt_of_p_times_4 is not 4 sqrt(p) rounded up",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
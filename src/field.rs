@@ -0,0 +1,572 @@
+//! Arithmetic in the base field.
+//!
+//! The curve arithmetic and the class group action are written against the [`Field`] trait.
+//! [`ConstMontyForm`] and [`BoxedMontyForm`] implement it for any modulus, and faster
+//! implementations are used for some built-in primes, see [`csidh`](crate::csidh::csidh).
+//!
+//! This module also picks the fastest method available for the parameters for Legendre symbols
+//! and inversions: a binary GCD for the former, and fixed addition chains for exponentiations when
+//! the parameters provide some, generic square-and-multiply otherwise.
+//!
+//! [`BoxedMontyForm`]: crypto_bigint::modular::BoxedMontyForm
+
+use core::{
+    fmt::Debug,
+    ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+use crypto_bigint::{
+    BitOps, Integer, Random, Uint, Word, WrappingSub,
+    modular::{ConstMontyForm, ConstMontyParams},
+    rand_core::CryptoRngCore,
+    subtle::{self, Choice, ConditionallySelectable},
+};
+#[cfg(feature = "alloc")]
+use crypto_bigint::{BoxedUint, ConstantTimeSelect, RandomMod, modular::BoxedMontyForm};
+
+use crate::csidh_params::Params;
+
+#[cfg(target_pointer_width = "64")]
+mod fp512;
+
+#[cfg(target_pointer_width = "64")]
+pub use fp512::Fp512;
+
+/// Element of the base field F<sub>p</sub>.
+///
+/// Elements do not have to be [`Copy`], and the ones of a field chosen at runtime carry their
+/// modulus, so constants are built after another element of the same field. Implementations must
+/// represent elements in Montgomery form with R = 2<sup>64 LIMBS</sup>, as [`ConstMontyForm`] does,
+/// so that converting from one to the other only copies words.
+pub trait Field:
+    Clone
+    + Debug
+    + Eq
+    + Add<Output = Self>
+    + for<'a> Add<&'a Self, Output = Self>
+    + Sub<Output = Self>
+    + for<'a> Sub<&'a Self, Output = Self>
+    + Mul<Output = Self>
+    + for<'a> Mul<&'a Self, Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + for<'a> AddAssign<&'a Self>
+    + SubAssign
+    + for<'a> SubAssign<&'a Self>
+    + MulAssign
+    + for<'a> MulAssign<&'a Self>
+{
+    /// Returns 0, in the field of `other`.
+    fn zero_like(other: &Self) -> Self;
+
+    /// Returns 1, in the field of `other`.
+    fn one_like(other: &Self) -> Self;
+
+    /// Returns the element `x`, in the field of `other`.
+    fn from_u64_like(x: u64, other: &Self) -> Self;
+
+    /// Returns a random element, in the field of `other`.
+    fn random_like(other: &Self, rng: &mut impl CryptoRngCore) -> Self;
+
+    /// Returns the words of the Montgomery form of `self`, least significant first.
+    fn montgomery_words(&self) -> &[Word];
+
+    /// Returns the element of Montgomery form `words`, in the field of `other`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `words` does not have the number of words of the field.
+    fn from_montgomery_words(words: &[Word], other: &Self) -> Self;
+
+    /// Returns `self * self`.
+    fn square(&self) -> Self;
+
+    /// Returns `self + self`.
+    fn double(&self) -> Self {
+        self.clone() + self
+    }
+
+    /// Returns `self` to the power of the `bits` lowest bits of `exponent`, which is public.
+    fn pow_bounded_exp<I: Integer>(&self, exponent: &I, bits: u32) -> Self {
+        let mut result = Self::one_like(self);
+        for i in (0..bits).rev() {
+            result = result.square();
+            if exponent.bit_vartime(i) {
+                result *= self;
+            }
+        }
+        result
+    }
+
+    /// Returns whether `self` and `other` are equal, in constant time.
+    fn ct_eq(&self, other: &Self) -> Choice;
+
+    /// Returns `b` if `choice` is set, `a` otherwise, in constant time.
+    fn ct_select(a: &Self, b: &Self, choice: Choice) -> Self;
+
+    /// Swaps `a` and `b` if `choice` is set, in constant time.
+    fn ct_swap(a: &mut Self, b: &mut Self, choice: Choice) {
+        let a_or_b = Self::ct_select(a, b, choice);
+        *b = Self::ct_select(b, a, choice);
+        *a = a_or_b;
+    }
+
+    /// Returns whether `self` is a nonzero square, in constant time.
+    fn is_square(&self) -> Choice;
+
+    /// Returns the Legendre symbol of `self`: 1 if it is a nonzero square, -1 if it is not a
+    /// square and 0 if it is zero.
+    ///
+    /// Only to be used on public values.
+    fn legendre_vartime(&self) -> i8;
+}
+
+/// Copies `words` into a `Uint<LIMBS>`.
+///
+/// # Panics
+///
+/// Panics if `words` does not have `LIMBS` words.
+fn copy_words<const LIMBS: usize>(words: &[Word]) -> Uint<LIMBS> {
+    let mut copy = [0; LIMBS];
+    copy.copy_from_slice(words);
+    Uint::from_words(copy)
+}
+
+impl<const LIMBS: usize, MOD: ConstMontyParams<LIMBS>> Field for ConstMontyForm<MOD, LIMBS> {
+    fn zero_like(_: &Self) -> Self {
+        ConstMontyForm::ZERO
+    }
+
+    fn one_like(_: &Self) -> Self {
+        ConstMontyForm::ONE
+    }
+
+    fn from_u64_like(x: u64, _: &Self) -> Self {
+        ConstMontyForm::new(&Uint::from(x))
+    }
+
+    fn random_like(_: &Self, rng: &mut impl CryptoRngCore) -> Self {
+        ConstMontyForm::new(&Uint::random(rng))
+    }
+
+    fn montgomery_words(&self) -> &[Word] {
+        self.as_montgomery().as_words()
+    }
+
+    fn from_montgomery_words(words: &[Word], _: &Self) -> Self {
+        ConstMontyForm::from_montgomery(copy_words(words))
+    }
+
+    fn square(&self) -> Self {
+        ConstMontyForm::square(self)
+    }
+
+    fn double(&self) -> Self {
+        ConstMontyForm::double(self)
+    }
+
+    fn ct_eq(&self, other: &Self) -> Choice {
+        subtle::ConstantTimeEq::ct_eq(self, other)
+    }
+
+    fn ct_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        ConstMontyForm::conditional_select(a, b, choice)
+    }
+
+    fn ct_swap(a: &mut Self, b: &mut Self, choice: Choice) {
+        ConstMontyForm::conditional_swap(a, b, choice);
+    }
+
+    fn is_square(&self) -> Choice {
+        // The binary GCD is several times faster than Euler's criterion, even with an addition
+        // chain.
+        let (negative, nonzero) = jacobi(self.retrieve(), MOD::MODULUS.get());
+        nonzero & !negative
+    }
+
+    fn legendre_vartime(&self) -> i8 {
+        jacobi_vartime(self.retrieve(), MOD::MODULUS.get())
+    }
+}
+
+/// Elements of a field chosen at runtime, which share their Montgomery parameters.
+///
+/// `BoxedMontyForm` can only be built from a Montgomery form along with a copy of its parameters,
+/// so constants and selections clone them. A swap only selects once, the other element being
+/// derived from the sum of both.
+#[cfg(feature = "alloc")]
+impl Field for BoxedMontyForm {
+    fn zero_like(other: &Self) -> Self {
+        BoxedMontyForm::zero(other.params().clone())
+    }
+
+    fn one_like(other: &Self) -> Self {
+        BoxedMontyForm::one(other.params().clone())
+    }
+
+    fn from_u64_like(x: u64, other: &Self) -> Self {
+        let precision = other.bits_precision();
+        let x = BoxedUint::from(x);
+        let x = if x.bits_precision() < precision {
+            x.widen(precision)
+        } else {
+            x.shorten(precision)
+        };
+        BoxedMontyForm::new(x, other.params().clone())
+    }
+
+    fn random_like(other: &Self, rng: &mut impl CryptoRngCore) -> Self {
+        let modulus = other.params().modulus().as_nz_ref();
+        BoxedMontyForm::new(BoxedUint::random_mod(rng, modulus), other.params().clone())
+    }
+
+    fn montgomery_words(&self) -> &[Word] {
+        self.as_montgomery().as_words()
+    }
+
+    fn from_montgomery_words(words: &[Word], other: &Self) -> Self {
+        assert_eq!(
+            words.len(),
+            other.as_montgomery().nlimbs(),
+            "Wrong number of words"
+        );
+        BoxedMontyForm::from_montgomery(
+            BoxedUint::from_words(words.iter().copied()),
+            other.params().clone(),
+        )
+    }
+
+    fn square(&self) -> Self {
+        BoxedMontyForm::square(self)
+    }
+
+    fn double(&self) -> Self {
+        BoxedMontyForm::double(self)
+    }
+
+    fn ct_eq(&self, other: &Self) -> Choice {
+        subtle::ConstantTimeEq::ct_eq(self.as_montgomery(), other.as_montgomery())
+    }
+
+    fn ct_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        BoxedMontyForm::from_montgomery(
+            ConstantTimeSelect::ct_select(a.as_montgomery(), b.as_montgomery(), choice),
+            a.params().clone(),
+        )
+    }
+
+    fn ct_swap(a: &mut Self, b: &mut Self, choice: Choice) {
+        let sum = a.clone() + &*b;
+        *a = Self::ct_select(a, b, choice);
+        *b = sum - &*a;
+    }
+
+    fn is_square(&self) -> Choice {
+        let (negative, nonzero) = jacobi(self.retrieve(), self.params().modulus().as_ref().clone());
+        nonzero & !negative
+    }
+
+    fn legendre_vartime(&self) -> i8 {
+        jacobi_vartime(self.retrieve(), self.params().modulus().as_ref().clone())
+    }
+}
+
+/// Width of the windows of the addition chains.
+const WINDOW: u32 = 5;
+
+/// Step of an [`AdditionChain`]: a number of squarings followed by a multiplication by an odd
+/// power of the base, smaller than 2<sup>5</sup>, or by nothing if it is 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Step {
+    squarings: u16,
+    digit: u8,
+}
+
+/// Fixed addition chain computing a public exponent, derived from its sliding-window
+/// decomposition.
+///
+/// Chains are built at compile time with [`AdditionChain::steps`], whose length is given by
+/// [`AdditionChain::len`]:
+///
+/// ```
+/// use csidh::{AdditionChain, Step, Uint};
+///
+/// const EXPONENT: Uint<1> = Uint::from_u64(209);
+/// const STEPS: [Step; AdditionChain::len(&EXPONENT)] = AdditionChain::steps(&EXPONENT);
+/// const CHAIN: AdditionChain = AdditionChain::new(&STEPS);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AdditionChain {
+    steps: &'static [Step],
+}
+
+impl AdditionChain {
+    /// Constructs an addition chain from its steps.
+    pub const fn new(steps: &'static [Step]) -> Self {
+        Self { steps }
+    }
+
+    /// Returns the number of steps of the addition chain of `exponent`.
+    pub const fn len<const LIMBS: usize>(exponent: &Uint<LIMBS>) -> usize {
+        let mut len = 0;
+        let mut remaining = exponent.bits_vartime();
+        while remaining > 0 {
+            (_, remaining) = next_step(exponent, remaining);
+            len += 1;
+        }
+        len
+    }
+
+    /// Returns the steps of the addition chain of `exponent`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `LEN` is not [`AdditionChain::len`] of `exponent`.
+    pub const fn steps<const LIMBS: usize, const LEN: usize>(
+        exponent: &Uint<LIMBS>,
+    ) -> [Step; LEN] {
+        let mut steps = [Step {
+            squarings: 0,
+            digit: 0,
+        }; LEN];
+        let mut len = 0;
+        let mut remaining = exponent.bits_vartime();
+        while remaining > 0 {
+            assert!(len < LEN, "Wrong addition chain length");
+            (steps[len], remaining) = next_step(exponent, remaining);
+            len += 1;
+        }
+        assert!(len == LEN, "Wrong addition chain length");
+        steps
+    }
+
+    /// Returns `x` to the power of the exponent of the chain.
+    ///
+    /// The sequence of operations only depends on the chain.
+    pub fn pow<const LIMBS: usize, MOD: ConstMontyParams<LIMBS>>(
+        &self,
+        x: ConstMontyForm<MOD, LIMBS>,
+    ) -> ConstMontyForm<MOD, LIMBS> {
+        self.pow_field(&x)
+    }
+
+    /// Same as [`AdditionChain::pow`], for any [`Field`].
+    pub(crate) fn pow_field<F: Field>(&self, x: &F) -> F {
+        let Some((first, steps)) = self.steps.split_first() else {
+            return F::one_like(x);
+        };
+
+        // x, x^3, x^5, ..., x^(2^WINDOW - 1).
+        let x_square = x.square();
+        let mut odd_powers: [F; 1 << (WINDOW - 1)] = core::array::from_fn(|_| x.clone());
+        for i in 1..odd_powers.len() {
+            odd_powers[i] = odd_powers[i - 1].clone() * &x_square;
+        }
+
+        // The first step squares 1, which is skipped.
+        let mut result = odd_powers[first.digit as usize / 2].clone();
+        for step in steps {
+            for _ in 0..step.squarings {
+                result = result.square();
+            }
+            if step.digit != 0 {
+                result *= &odd_powers[step.digit as usize / 2];
+            }
+        }
+        result
+    }
+}
+
+/// Returns the next step of the addition chain of `exponent`, whose `remaining` lowest bits are
+/// left to process, along with the number of bits left after it.
+const fn next_step<const LIMBS: usize>(exponent: &Uint<LIMBS>, remaining: u32) -> (Step, u32) {
+    let mut top = remaining;
+    while top > 0 && !exponent.bit_vartime(top - 1) {
+        top -= 1;
+    }
+    if top == 0 {
+        return (
+            Step {
+                squarings: remaining as u16,
+                digit: 0,
+            },
+            0,
+        );
+    }
+
+    // The window is made of the bits from `bottom` to `top` - 1, and ends with a 1.
+    let mut bottom = top.saturating_sub(WINDOW);
+    while !exponent.bit_vartime(bottom) {
+        bottom += 1;
+    }
+    let mut digit = 0;
+    let mut i = top;
+    while i > bottom {
+        i -= 1;
+        digit = 2 * digit + exponent.bit_vartime(i) as u8;
+    }
+
+    let squarings = remaining - bottom;
+    assert!(squarings <= u16::MAX as u32, "Too many squarings");
+    (
+        Step {
+            squarings: squarings as u16,
+            digit,
+        },
+        bottom,
+    )
+}
+
+/// Fixed addition chains for the exponents used in the base field F<sub>p</sub>.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AdditionChains {
+    p_minus_1_over_2: AdditionChain,
+    p_minus_2: AdditionChain,
+    p_plus_1_over_4: AdditionChain,
+}
+
+impl AdditionChains {
+    /// Constructs the addition chains for (p - 1) / 2, p - 2 and (p + 1) / 4.
+    pub const fn new(
+        p_minus_1_over_2: AdditionChain,
+        p_minus_2: AdditionChain,
+        p_plus_1_over_4: AdditionChain,
+    ) -> Self {
+        Self {
+            p_minus_1_over_2,
+            p_minus_2,
+            p_plus_1_over_4,
+        }
+    }
+
+    /// Returns the addition chain for (p - 1) / 2, which gives Legendre symbols.
+    pub const fn p_minus_1_over_2(&self) -> AdditionChain {
+        self.p_minus_1_over_2
+    }
+
+    /// Returns the addition chain for p - 2, which gives inverses.
+    pub const fn p_minus_2(&self) -> AdditionChain {
+        self.p_minus_2
+    }
+
+    /// Returns the addition chain for (p + 1) / 4, which gives square roots, since p = 3 mod 4.
+    pub const fn p_plus_1_over_4(&self) -> AdditionChain {
+        self.p_plus_1_over_4
+    }
+}
+
+/// Returns the inverse of `x`, or 0 if it is zero, in constant time.
+pub fn inv<P: Params, F: Field>(params: &P, x: &F) -> F {
+    match params.addition_chains() {
+        Some(chains) => chains.p_minus_2().pow_field(x),
+        None => {
+            let p_minus_2 = params.p().wrapping_sub(&params.integer(2));
+            x.pow_bounded_exp(&p_minus_2, p_minus_2.bits_vartime())
+        }
+    }
+}
+
+/// Returns the least significant word of `x`.
+fn low_word<I: Integer>(x: &I) -> Word {
+    x.as_ref()[0].0
+}
+
+/// Computes the Jacobi symbol (a / b) of `a` < `b`, `b` being odd, in constant time, with the
+/// binary GCD of <i><a href=https://eprint.iacr.org/2020/972>
+/// Pornin, T.: Optimized binary GCD for modular inversion. Cryptology ePrint Archive, Paper
+/// 2020/972 (2020)</a></i>, in the spirit of the divsteps of <i>
+/// <a href=https://eprint.iacr.org/2019/266>
+/// Bernstein, D.J., Yang, B.-Y.: Fast constant-time gcd computation and modular inversion. IACR
+/// TCHES 2019(3). pp. 340–398 (2019)</a></i>.
+///
+/// Both must have the same precision. Returns whether the symbol is -1, and whether it is
+/// nonzero.
+fn jacobi<I: Integer>(a: I, b: I) -> (Choice, Choice) {
+    let mut a = a;
+    let mut b = b;
+    let mut negative = 0;
+
+    // Each iteration removes at least one bit from a or b, with a being 0 at the end.
+    for _ in 0..2 * b.bits_precision() {
+        let a_odd = a.is_odd();
+        let swap = a_odd & a.ct_lt(&b);
+        // Quadratic reciprocity, both a and b being odd when they are swapped.
+        let both_3_mod_4 = (low_word(&a) & low_word(&b)) >> 1 & 1;
+        negative ^= u8::conditional_select(&0, &(both_3_mod_4 as u8), swap);
+        I::ct_swap(&mut a, &mut b, swap);
+
+        a = I::ct_select(&a, &a.wrapping_sub(&b), a_odd);
+        a = a.wrapping_shr_vartime(1);
+        // (2 / b) is -1 when b = 3 or 5 mod 8.
+        let b_word = low_word(&b);
+        negative ^= ((b_word >> 1 ^ b_word >> 2) & 1) as u8;
+    }
+
+    let one = I::one_like(&b);
+    (Choice::from(negative), b.ct_eq(&one))
+}
+
+/// Computes the Jacobi symbol (a / b) of `a` < `b`, `b` being odd, in variable time.
+fn jacobi_vartime<I: Integer>(a: I, b: I) -> i8 {
+    let mut a = a;
+    let mut b = b;
+    let mut symbol = 1;
+    while !bool::from(a.is_zero()) {
+        let zeros = a.trailing_zeros_vartime();
+        a = a.wrapping_shr_vartime(zeros);
+        let b_mod_8 = low_word(&b) & 7;
+        if zeros % 2 == 1 && (b_mod_8 == 3 || b_mod_8 == 5) {
+            symbol = -symbol;
+        }
+        if a < b {
+            if low_word(&a) & 3 == 3 && b_mod_8 & 3 == 3 {
+                symbol = -symbol;
+            }
+            core::mem::swap(&mut a, &mut b);
+        }
+        a = a.wrapping_sub(&b);
+    }
+    if b == I::one_like(&b) { symbol } else { 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        CsidhParams,
+        csidh_params::{csidh_512, toy},
+    };
+
+    #[test]
+    fn addition_chains_match_pow() {
+        let params = CsidhParams::CSIDH_512;
+        let chains = params.addition_chains().unwrap();
+        let x = ConstMontyForm::<csidh_512::MOD, { csidh_512::LIMBS }>::new(&Uint::from(12345u32));
+        let p_minus_1_over_2 = params.p_minus_1_over_2();
+        let p_minus_2 = p_minus_1_over_2.shl_vartime(1).wrapping_sub(&Uint::ONE);
+        let p_plus_1_over_4 = p_minus_1_over_2.wrapping_add(&Uint::ONE).shr_vartime(1);
+
+        assert_eq!(chains.p_minus_1_over_2().pow(x), x.pow(&p_minus_1_over_2));
+        assert_eq!(chains.p_minus_2().pow(x), x.pow(&p_minus_2));
+        assert_eq!(chains.p_plus_1_over_4().pow(x), x.pow(&p_plus_1_over_4));
+        assert_eq!(inv(&params, &x) * x, ConstMontyForm::ONE);
+    }
+
+    #[test]
+    fn jacobi_matches_euler_criterion() {
+        let params = toy::TOY;
+        for i in 0..200u32 {
+            let x = ConstMontyForm::<toy::MOD, { toy::LIMBS }>::new(&Uint::from(i * i + 7 * i));
+            let euler = x.pow(&params.p_minus_1_over_2());
+            let expected = if euler == ConstMontyForm::ONE {
+                1
+            } else if euler == ConstMontyForm::ZERO {
+                0
+            } else {
+                -1
+            };
+            assert_eq!(x.legendre_vartime(), expected);
+            assert_eq!(bool::from(x.is_square()), expected == 1);
+        }
+    }
+}
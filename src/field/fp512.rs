@@ -0,0 +1,307 @@
+//! Arithmetic modulo the CSIDH-512 prime, specialized for 8 limbs of 64 bits.
+//!
+//! Elements are kept fully reduced in Montgomery form with R = 2<sup>512</sup>, like
+//! [`ConstMontyForm`] does, so that both representations convert to each other without any
+//! computation. The loops over the limbs have fixed bounds, so that the compiler unrolls them.
+
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use crypto_bigint::{
+    Limb, Random, Uint, Word,
+    modular::{ConstMontyForm, ConstMontyParams},
+    rand_core::CryptoRngCore,
+    subtle::{Choice, ConstantTimeEq},
+};
+
+use super::Field;
+use crate::csidh_params::csidh_512::{LIMBS, MOD};
+
+/// The CSIDH-512 prime.
+const P: [u64; LIMBS] = MOD::MODULUS.as_ref().to_words();
+
+/// -p<sup>-1</sup> mod 2<sup>64</sup>.
+const P_INV: u64 = MOD::MOD_NEG_INV.0;
+
+/// Element of the CSIDH-512 base field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fp512([u64; LIMBS]);
+
+impl Fp512 {
+    /// The additive identity.
+    const ZERO: Self = Self([0; LIMBS]);
+
+    /// Returns whether `modulus`, given by its limbs, is the CSIDH-512 prime.
+    pub fn handles(modulus: &[Limb]) -> bool {
+        modulus.iter().map(|limb| limb.0).eq(P)
+    }
+
+    /// Converts `x`, whose modulus must be the CSIDH-512 prime.
+    pub fn from_field<F: Field>(x: &F) -> Self {
+        Self::from_montgomery_words(x.montgomery_words(), &Self::ZERO)
+    }
+
+    /// Converts to an element of the field of `other`, whose modulus must be the CSIDH-512 prime.
+    pub fn to_field<F: Field>(self, other: &F) -> F {
+        F::from_montgomery_words(&self.0, other)
+    }
+
+    /// Returns the CSIDH-512 [`ConstMontyForm`] with the same representation.
+    const fn monty(self) -> ConstMontyForm<MOD, LIMBS> {
+        ConstMontyForm::from_montgomery(Uint::from_words(self.0))
+    }
+
+    /// Returns `self * rhs`.
+    ///
+    /// This is the CIOS Montgomery multiplication, without the extra word it needs in general:
+    /// the most significant bit of p is zero, so the intermediate results fit in 8 words, as
+    /// noticed in <i><a href=https://hackmd.io/@gnark/modular_multiplication>
+    /// Botrel, G., El Housni, Y.: Faster Montgomery multiplication and multi-scalar-multiplication
+    /// for SNARKs (2023)</a></i>.
+    #[inline(always)]
+    fn mul(&self, rhs: &Self) -> Self {
+        let a = &self.0;
+        let b = &rhs.0;
+        let mut t = [0u64; LIMBS];
+        for &word in b {
+            let (low, mut carry_a) = mac(t[0], a[0], word, 0);
+            let m = low.wrapping_mul(P_INV);
+            let (_, mut carry_m) = mac(low, m, P[0], 0);
+            for j in 1..LIMBS {
+                let (low, carry) = mac(t[j], a[j], word, carry_a);
+                carry_a = carry;
+                (t[j - 1], carry_m) = mac(low, m, P[j], carry_m);
+            }
+            t[LIMBS - 1] = carry_m + carry_a;
+        }
+
+        // The result is below 2p, which is below 2^512.
+        Self(reduce_once(t))
+    }
+}
+
+impl Field for Fp512 {
+    fn zero_like(_: &Self) -> Self {
+        Self::ZERO
+    }
+
+    fn one_like(_: &Self) -> Self {
+        Self(MOD::ONE.to_words())
+    }
+
+    fn from_u64_like(x: u64, _: &Self) -> Self {
+        Self::from_field(&ConstMontyForm::<MOD, LIMBS>::new(&Uint::from(x)))
+    }
+
+    fn random_like(_: &Self, rng: &mut impl CryptoRngCore) -> Self {
+        Self::from_field(&ConstMontyForm::<MOD, LIMBS>::new(&Uint::random(rng)))
+    }
+
+    fn montgomery_words(&self) -> &[Word] {
+        &self.0
+    }
+
+    fn from_montgomery_words(words: &[Word], _: &Self) -> Self {
+        let mut copy = [0; LIMBS];
+        copy.copy_from_slice(words);
+        Self(copy)
+    }
+
+    #[inline(always)]
+    fn square(&self) -> Self {
+        self.mul(self)
+    }
+
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+
+    fn ct_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mask = (choice.unwrap_u8() as u64).wrapping_neg();
+        Self(select(&a.0, &b.0, mask))
+    }
+
+    fn is_square(&self) -> Choice {
+        self.monty().is_square()
+    }
+
+    fn legendre_vartime(&self) -> i8 {
+        self.monty().legendre_vartime()
+    }
+}
+
+/// Returns the low and high words of `t + a * b + carry`, which cannot overflow.
+#[inline(always)]
+const fn mac(t: u64, a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let result = t as u128 + a as u128 * b as u128 + carry as u128;
+    (result as u64, (result >> 64) as u64)
+}
+
+/// Returns `a - p` if it does not underflow, `a` otherwise, in constant time.
+#[inline(always)]
+fn reduce_once(a: [u64; LIMBS]) -> [u64; LIMBS] {
+    let (difference, borrow) = sub_words(&a, &P);
+    select(&difference, &a, borrow)
+}
+
+/// Returns `a + b`, dropping the carry out of the last word.
+#[inline(always)]
+fn add_words(a: &[u64; LIMBS], b: &[u64; LIMBS]) -> [u64; LIMBS] {
+    let mut result = [0; LIMBS];
+    let mut carry = 0;
+    for i in 0..LIMBS {
+        let (sum, carry_1) = a[i].overflowing_add(b[i]);
+        let (sum, carry_2) = sum.overflowing_add(carry);
+        result[i] = sum;
+        carry = (carry_1 | carry_2) as u64;
+    }
+    result
+}
+
+/// Returns `a - b` and whether it underflows, as a mask.
+#[inline(always)]
+fn sub_words(a: &[u64; LIMBS], b: &[u64; LIMBS]) -> ([u64; LIMBS], u64) {
+    let mut result = [0; LIMBS];
+    let mut borrow = 0;
+    for i in 0..LIMBS {
+        let (difference, borrow_1) = a[i].overflowing_sub(b[i]);
+        let (difference, borrow_2) = difference.overflowing_sub(borrow);
+        result[i] = difference;
+        borrow = (borrow_1 | borrow_2) as u64;
+    }
+    (result, borrow.wrapping_neg())
+}
+
+/// Returns `b` where `mask` is all ones, `a` where it is zero.
+#[inline(always)]
+fn select(a: &[u64; LIMBS], b: &[u64; LIMBS], mask: u64) -> [u64; LIMBS] {
+    let mut result = [0; LIMBS];
+    for i in 0..LIMBS {
+        result[i] = a[i] ^ (mask & (a[i] ^ b[i]));
+    }
+    result
+}
+
+impl Add for Fp512 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        // Both are below p, so the sum is below 2p, which is below 2^512.
+        Self(reduce_once(add_words(&self.0, &rhs.0)))
+    }
+}
+
+impl Sub for Fp512 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        // p is added back when the difference underflows, and the carry it produces is dropped.
+        let (difference, borrow) = sub_words(&self.0, &rhs.0);
+        Self(add_words(&difference, &P.map(|word| word & borrow)))
+    }
+}
+
+impl Mul for Fp512 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Fp512::mul(&self, &rhs)
+    }
+}
+
+impl Add<&Fp512> for Fp512 {
+    type Output = Self;
+
+    fn add(self, rhs: &Self) -> Self {
+        self + *rhs
+    }
+}
+
+impl Sub<&Fp512> for Fp512 {
+    type Output = Self;
+
+    fn sub(self, rhs: &Self) -> Self {
+        self - *rhs
+    }
+}
+
+impl Mul<&Fp512> for Fp512 {
+    type Output = Self;
+
+    fn mul(self, rhs: &Self) -> Self {
+        self * *rhs
+    }
+}
+
+impl Neg for Fp512 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::ZERO - self
+    }
+}
+
+impl AddAssign<&Fp512> for Fp512 {
+    fn add_assign(&mut self, rhs: &Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign<&Fp512> for Fp512 {
+    fn sub_assign(&mut self, rhs: &Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign<&Fp512> for Fp512 {
+    fn mul_assign(&mut self, rhs: &Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl AddAssign for Fp512 {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Fp512 {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign for Fp512 {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handles_the_csidh_512_prime_only() {
+        assert!(Fp512::handles(MOD::MODULUS.as_limbs()));
+        assert!(!Fp512::handles(
+            crate::csidh_params::toy::MOD::MODULUS.as_limbs()
+        ));
+    }
+
+    #[test]
+    fn matches_const_monty_form() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let a = ConstMontyForm::<MOD, LIMBS>::new(&Uint::random(&mut rng));
+            let b = ConstMontyForm::<MOD, LIMBS>::new(&Uint::random(&mut rng));
+            let (x, y) = (Fp512::from_field(&a), Fp512::from_field(&b));
+            assert_eq!((x * y).monty(), a * b);
+            assert_eq!(x.square().monty(), a.square());
+            assert_eq!((x + y).monty(), a + b);
+            assert_eq!((x - y).monty(), a - b);
+            assert_eq!((-x).monty(), -a);
+        }
+        assert_eq!(-Fp512::ZERO, Fp512::ZERO);
+        assert_eq!(Fp512::one_like(&Fp512::ZERO).monty(), ConstMontyForm::ONE);
+    }
+}
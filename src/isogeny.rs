@@ -0,0 +1,189 @@
+//! Isogenies of odd prime degree between Montgomery curves.
+
+mod polynomial;
+mod sqrt_velu;
+
+use crypto_bigint::U64;
+
+use crate::{
+    csidh_params::Params, field::Field, montgomery_curve::MontgomeryCurve,
+    montgomery_point::MontgomeryPoint,
+};
+use sqrt_velu::SqrtVelu;
+
+/// Isogeny of odd prime degree, given by a generator of its kernel.
+///
+/// Degrees below the [`sqrt_velu_threshold`](crate::CsidhParams::sqrt_velu_threshold) of the
+/// parameters are computed with Vélu's formulas, in O(ℓ) operations, and the other ones with the
+/// square-root Vélu formulas, in Õ(√ℓ) operations. Both work on projective coordinates and
+/// need no inversion.
+pub struct Isogeny<P: Params, F: Field> {
+    domain: MontgomeryCurve<P, F>,
+    kernel: MontgomeryPoint<P, F>,
+    degree: u64,
+    sqrt_velu: Option<SqrtVelu<P, F>>,
+}
+
+impl<P: Params, F: Field> Isogeny<P, F> {
+    /// Constructs the isogeny of degree `degree` from `domain` whose kernel is generated by
+    /// `kernel`.
+    pub fn new(domain: MontgomeryCurve<P, F>, kernel: MontgomeryPoint<P, F>, degree: u64) -> Self {
+        let sqrt_velu = if degree >= domain.params().sqrt_velu_threshold() {
+            SqrtVelu::new(&domain, &kernel, degree)
+        } else {
+            None
+        };
+        Self {
+            domain,
+            kernel,
+            degree,
+            sqrt_velu,
+        }
+    }
+
+    /// Computes the codomain of the isogeny.
+    ///
+    /// Vélu's formulas are used in their twisted Edwards form, from <i>
+    /// <a href=https://eprint.iacr.org/2018/782>
+    /// [Meyer, M., Reith, S.: A faster way to the CSIDH. In: INDOCRYPT 2018, LNCS 11356.
+    /// pp. 137–152. Springer (2018)]</a></i>: the domain is birationally equivalent to the
+    /// twisted Edwards curve with a = A + 2C and d = A - 2C, and the codomain to the one with
+    /// a' = a^ℓ (∏ (X<sub>i</sub> + Z<sub>i</sub>))^8 and d' = d^ℓ (∏ (X<sub>i</sub> -
+    /// Z<sub>i</sub>))^8, for the multiples (X<sub>i</sub> : Z<sub>i</sub>) of the kernel up to
+    /// (ℓ - 1) / 2. Then (A' + 2C' : 4C') = (a' : a' - d').
+    pub fn codomain(&self) -> MontgomeryCurve<P, F> {
+        if let Some(sqrt_velu) = &self.sqrt_velu {
+            return sqrt_velu.codomain();
+        }
+
+        let mut plus = F::one_like(self.domain.c24());
+        let mut minus = plus.clone();
+        for multiple in self.kernel.clone().multiples(self.degree / 2) {
+            plus *= multiple.X().clone() + multiple.Z();
+            minus *= multiple.X().clone() - multiple.Z();
+        }
+
+        codomain(&self.domain, self.degree, plus, minus)
+    }
+
+    /// Computes the image of `point` on `codomain`.
+    pub fn image(
+        &self,
+        codomain: &MontgomeryCurve<P, F>,
+        point: &MontgomeryPoint<P, F>,
+    ) -> MontgomeryPoint<P, F> {
+        if let Some(sqrt_velu) = &self.sqrt_velu {
+            return sqrt_velu.image(codomain, point);
+        }
+
+        let x = point.X();
+        let z = point.Z();
+        let x_plus_z = x.clone() + z;
+        let x_minus_z = x.clone() - z;
+
+        let mut temp_x = F::one_like(x);
+        let mut temp_z = temp_x.clone();
+        for multiple in self.kernel.clone().multiples(self.degree / 2) {
+            let xi = multiple.X();
+            let zi = multiple.Z();
+
+            let a = x_minus_z.clone() * (xi.clone() + zi);
+            let b = x_plus_z.clone() * (xi.clone() - zi);
+
+            temp_x *= a.clone() + &b;
+            temp_z *= a - b;
+        }
+
+        let x_prime = x.clone() * temp_x.square();
+        let z_prime = z.clone() * temp_z.square();
+
+        MontgomeryPoint::new(codomain.clone(), x_prime, z_prime)
+    }
+}
+
+/// Returns the codomain of an isogeny of degree `degree` from `domain`, given the values `plus` and
+/// `minus` that the twisted Edwards coefficients a and d are multiplied by, before their eighth
+/// power.
+fn codomain<P: Params, F: Field>(
+    domain: &MontgomeryCurve<P, F>,
+    degree: u64,
+    plus: F,
+    minus: F,
+) -> MontgomeryCurve<P, F> {
+    let bits = u64::BITS - degree.leading_zeros();
+    let degree = U64::from_u64(degree);
+    let a = domain.a24_plus();
+    let d = a.clone() - domain.c24();
+    let a_prime = a.pow_bounded_exp(&degree, bits) * plus.square().square().square();
+    let d_prime = d.pow_bounded_exp(&degree, bits) * minus.square().square().square();
+    let c_prime = a_prime.clone() - d_prime;
+    MontgomeryCurve::new_projective(domain.params(), a_prime, c_prime)
+}
+
+#[cfg(test)]
+mod tests {
+    use crypto_bigint::{
+        Uint,
+        modular::{ConstMontyForm, ConstMontyParams},
+    };
+
+    use super::*;
+    use crate::{
+        CsidhParams,
+        csidh_params::{csidh_512, toy},
+    };
+
+    /// Checks that both formulas compute the same codomain and image for the isogeny of degree
+    /// `lis[index]` from the curve y^2 = x^3 + x.
+    fn check<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>>(
+        params: CsidhParams<LIMBS, N, MOD>,
+        index: usize,
+    ) {
+        let mut rng = rand::thread_rng();
+        let zero = ConstMontyForm::<MOD, LIMBS>::ZERO;
+        let velu = MontgomeryCurve::new(params.with_sqrt_velu_threshold(u64::MAX), zero);
+        let sqrt_velu = MontgomeryCurve::new(params.with_sqrt_velu_threshold(0), zero);
+
+        let lis = params.lis();
+        let mut cofactor = Uint::<LIMBS>::from(4u32);
+        for (i, li) in lis.iter().enumerate() {
+            if i != index {
+                cofactor *= Uint::<LIMBS>::from(*li);
+            }
+        }
+
+        let kernel = loop {
+            let point = velu.random_point(&mut rng) * &cofactor;
+            if !point.is_infinity() {
+                break point;
+            }
+        };
+        let point = velu.random_point(&mut rng);
+
+        let expected = Isogeny::new(velu, kernel.clone(), lis[index]);
+        let actual = Isogeny::new(sqrt_velu, kernel, lis[index]);
+        assert!(actual.sqrt_velu.is_some() || lis[index] < 5);
+
+        let codomain = expected.codomain();
+        assert_eq!(actual.codomain().a2(), codomain.a2());
+        assert_eq!(
+            actual.image(&codomain, &point),
+            expected.image(&codomain, &point)
+        );
+    }
+
+    #[test]
+    fn sqrt_velu_matches_velu() {
+        for index in 0..toy::N {
+            check(toy::TOY, index);
+        }
+    }
+
+    #[test]
+    fn sqrt_velu_matches_velu_large_degrees() {
+        // 587 is the largest prime of CSIDH-512, and 373 the second largest.
+        for index in [csidh_512::N - 1, csidh_512::N - 2] {
+            check(CsidhParams::CSIDH_512, index);
+        }
+    }
+}
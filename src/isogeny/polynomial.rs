@@ -0,0 +1,350 @@
+//! Dense polynomials over the base field.
+//!
+//! Polynomials are slices of coefficients, lowest degree first. Their degree is bounded so that
+//! they can live on the stack.
+
+use crate::field::Field;
+
+/// Maximal number of coefficients of a polynomial.
+pub const CAPACITY: usize = 65;
+
+/// Maximal number of leaves of a [`ProductTree`].
+pub const MAX_LEAVES: usize = 32;
+
+/// Number of levels of a [`ProductTree`] with [`MAX_LEAVES`] leaves.
+const DEPTH: usize = 6;
+
+/// Length below which products are computed with the schoolbook method.
+const KARATSUBA_THRESHOLD: usize = 16;
+
+/// Returns `LEN` zeros of the field of `like`.
+pub fn zeros<F: Field, const LEN: usize>(like: &F) -> [F; LEN] {
+    core::array::from_fn(|_| F::zero_like(like))
+}
+
+/// Sets `out` to `a * b`.
+///
+/// `out` must hold exactly `a.len() + b.len() - 1` coefficients, and `a` and `b` at most
+/// [`CAPACITY`] each.
+pub fn mul<F: Field>(a: &[F], b: &[F], out: &mut [F]) {
+    let n = a.len().max(b.len());
+    let m = a.len().min(b.len());
+
+    // Karatsuba splits both operands at the same place, which is only worth it when they have
+    // similar lengths.
+    if m < KARATSUBA_THRESHOLD || 2 * m < n {
+        schoolbook(a, b, out);
+        return;
+    }
+
+    let mut a_padded: [F; CAPACITY] = zeros(&a[0]);
+    a_padded[..a.len()].clone_from_slice(a);
+    let mut b_padded: [F; CAPACITY] = zeros(&a[0]);
+    b_padded[..b.len()].clone_from_slice(b);
+
+    let mut product: [F; 2 * CAPACITY] = zeros(&a[0]);
+    karatsuba(&a_padded[..n], &b_padded[..n], &mut product[..2 * n - 1]);
+    let len = out.len();
+    out.clone_from_slice(&product[..len]);
+}
+
+fn schoolbook<F: Field>(a: &[F], b: &[F], out: &mut [F]) {
+    out.fill(F::zero_like(&a[0]));
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            out[i + j] += x.clone() * y;
+        }
+    }
+}
+
+/// Sets `out` to `a * b`, `a` and `b` having the same length n, and `out` a length of 2n - 1.
+fn karatsuba<F: Field>(a: &[F], b: &[F], out: &mut [F]) {
+    let n = a.len();
+    if n < KARATSUBA_THRESHOLD {
+        schoolbook(a, b, out);
+        return;
+    }
+
+    let low = n / 2;
+    let high = n - low;
+    let (a0, a1) = a.split_at(low);
+    let (b0, b1) = b.split_at(low);
+
+    // out = a0 * b0 + (a1 * b1) X^(2 * low), the two products not overlapping.
+    karatsuba(a0, b0, &mut out[..2 * low - 1]);
+    out[2 * low - 1] = F::zero_like(&a[0]);
+    karatsuba(a1, b1, &mut out[2 * low..]);
+
+    // (a0 + a1) * (b0 + b1) - a0 * b0 - a1 * b1 is the middle term.
+    let mut a_sum: [F; CAPACITY] = zeros(&a[0]);
+    let mut b_sum: [F; CAPACITY] = zeros(&a[0]);
+    a_sum[..high].clone_from_slice(a1);
+    b_sum[..high].clone_from_slice(b1);
+    for i in 0..low {
+        a_sum[i] += &a0[i];
+        b_sum[i] += &b0[i];
+    }
+    let mut middle: [F; 2 * CAPACITY] = zeros(&a[0]);
+    karatsuba(&a_sum[..high], &b_sum[..high], &mut middle[..2 * high - 1]);
+    for i in 0..2 * low - 1 {
+        middle[i] -= &out[i];
+    }
+    for i in 0..2 * high - 1 {
+        middle[i] -= &out[2 * low + i];
+    }
+    for i in 0..2 * high - 1 {
+        out[low + i] += &middle[i];
+    }
+}
+
+/// Sets `out` to a pseudo-remainder of `a` modulo `divisor`: `a` times a power of the leading
+/// coefficient of `divisor`, modulo `divisor`.
+///
+/// The power only depends on the lengths of `a` and `divisor`, so that no inversion is needed.
+/// `out` must hold exactly `divisor.len() - 1` coefficients, and `a` at most [`CAPACITY`].
+fn rem<F: Field>(a: &[F], divisor: &[F], out: &mut [F]) {
+    let degree = divisor.len() - 1;
+    let lead = &divisor[degree];
+    if a.len() <= degree {
+        out[..a.len()].clone_from_slice(a);
+        out[a.len()..].fill(F::zero_like(lead));
+        return;
+    }
+
+    let mut remainder: [F; CAPACITY] = zeros(lead);
+    remainder[..a.len()].clone_from_slice(a);
+    for i in (degree..a.len()).rev() {
+        let top = remainder[i].clone();
+        for coefficient in &mut remainder[..i - degree] {
+            *coefficient *= lead;
+        }
+        for j in 0..degree {
+            remainder[i - degree + j] =
+                remainder[i - degree + j].clone() * lead - top.clone() * &divisor[j];
+        }
+    }
+    out.clone_from_slice(&remainder[..degree]);
+}
+
+/// Returns the offset and the length of the `index`-th node of the `level`-th level of a product
+/// tree of `leaves` polynomials of degree `degree`.
+///
+/// Each node is the product of the two nodes below it, the last node of a level being carried
+/// over as is when it has no sibling. Levels store their nodes next to each other.
+const fn node(leaves: usize, degree: usize, level: usize, index: usize) -> (usize, usize) {
+    let width = 1 << level;
+    let count = if leaves - index * width < width {
+        leaves - index * width
+    } else {
+        width
+    };
+    (index * (degree * width + 1), degree * count + 1)
+}
+
+/// Computes the nodes of the `level`-th level of a product tree from those of the level below.
+fn build_level<F: Field>(
+    below: &[F],
+    level_nodes: &mut [F],
+    leaves: usize,
+    degree: usize,
+    level: usize,
+) {
+    let nodes = leaves.div_ceil(1 << level);
+    let nodes_below = leaves.div_ceil(1 << (level - 1));
+    for index in 0..nodes {
+        let (offset, len) = node(leaves, degree, level, index);
+        let (left_offset, left_len) = node(leaves, degree, level - 1, 2 * index);
+        let left = &below[left_offset..left_offset + left_len];
+        if 2 * index + 1 < nodes_below {
+            let (right_offset, right_len) = node(leaves, degree, level - 1, 2 * index + 1);
+            let right = &below[right_offset..right_offset + right_len];
+            mul(left, right, &mut level_nodes[offset..offset + len]);
+        } else {
+            level_nodes[offset..offset + len].clone_from_slice(left);
+        }
+    }
+}
+
+/// Computes the product of the quadratic polynomials `factors` into `out`, which must hold
+/// exactly `2 * factors.len() + 1` coefficients.
+///
+/// `factors` must contain between 1 and [`MAX_LEAVES`] polynomials.
+pub fn product_of_quadratics<F: Field>(factors: &[[F; 3]], out: &mut [F]) {
+    let leaves = factors.len();
+    let mut below: [F; 2 * CAPACITY] = zeros(&factors[0][0]);
+    let mut current: [F; 2 * CAPACITY] = zeros(&factors[0][0]);
+    for (index, factor) in factors.iter().enumerate() {
+        let (offset, _) = node(leaves, 2, 0, index);
+        below[offset..offset + 3].clone_from_slice(factor);
+    }
+
+    let mut level = 0;
+    while leaves > 1 << level {
+        level += 1;
+        build_level(&below, &mut current, leaves, 2, level);
+        core::mem::swap(&mut below, &mut current);
+    }
+    out.clone_from_slice(&below[..2 * leaves + 1]);
+}
+
+/// Product tree of the polynomials Z<sub>i</sub> X - X<sub>i</sub>, used to evaluate polynomials
+/// at all the roots (X<sub>i</sub> : Z<sub>i</sub>) at once.
+pub struct ProductTree<F: Field> {
+    levels: [[F; CAPACITY]; DEPTH],
+    leaves: usize,
+    height: usize,
+}
+
+impl<F: Field> ProductTree<F> {
+    /// Builds the product tree of the polynomials Z X - X<sub>r</sub> for each projective root
+    /// (X<sub>r</sub> : Z) in `roots`.
+    ///
+    /// `roots` must contain between 1 and [`MAX_LEAVES`] elements, none of them at infinity.
+    pub fn new(roots: &[[F; 2]]) -> Self {
+        let leaves = roots.len();
+        let mut levels: [[F; CAPACITY]; DEPTH] = core::array::from_fn(|_| zeros(&roots[0][0]));
+        for (index, [x, z]) in roots.iter().enumerate() {
+            levels[0][2 * index] = -x.clone();
+            levels[0][2 * index + 1] = z.clone();
+        }
+
+        let mut height = 1;
+        while leaves > 1 << (height - 1) {
+            let (below, above) = levels.split_at_mut(height);
+            build_level(&below[height - 1], &mut above[0], leaves, 1, height);
+            height += 1;
+        }
+
+        Self {
+            levels,
+            leaves,
+            height,
+        }
+    }
+
+    /// Returns the product of the evaluations of `polynomial` at every root of the tree, up to a
+    /// factor that only depends on the tree and on the length of `polynomial`.
+    ///
+    /// This is a remainder tree: `polynomial` is reduced modulo the root of the tree, then
+    /// modulo each node down to the leaves. At the degrees used by CSIDH, the reductions are
+    /// computed with schoolbook pseudo-divisions.
+    pub fn product_of_evaluations(&self, polynomial: &[F]) -> F {
+        // Remainders are stored with the same layout as the nodes, each remainder having one
+        // coefficient less than its node.
+        let like = &self.levels[0][0];
+        let mut above: [F; CAPACITY] = zeros(like);
+        let mut current: [F; CAPACITY] = zeros(like);
+
+        let top = self.height - 1;
+        let (_, len) = node(self.leaves, 1, top, 0);
+        rem(polynomial, &self.levels[top][..len], &mut above[..len - 1]);
+
+        for level in (0..top).rev() {
+            for index in 0..self.leaves.div_ceil(1 << level) {
+                let (offset, len) = node(self.leaves, 1, level, index);
+                let (parent_offset, parent_len) = node(self.leaves, 1, level + 1, index / 2);
+                rem(
+                    &above[parent_offset..parent_offset + parent_len - 1],
+                    &self.levels[level][offset..offset + len],
+                    &mut current[offset..offset + len - 1],
+                );
+            }
+            core::mem::swap(&mut above, &mut current);
+        }
+
+        let mut product = F::one_like(like);
+        for index in 0..self.leaves {
+            let (offset, _) = node(self.leaves, 1, 0, index);
+            product *= &above[offset];
+        }
+        product
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crypto_bigint::{U64, Uint, impl_modulus, modular::ConstMontyForm};
+
+    use super::*;
+
+    impl_modulus!(Prime, U64, "00000d7f8cebc633");
+
+    type Fp = ConstMontyForm<Prime, { U64::LIMBS }>;
+
+    fn element(x: u64) -> Fp {
+        Fp::new(&Uint::from_u64(x))
+    }
+
+    fn evaluate(polynomial: &[Fp], x: Fp) -> Fp {
+        polynomial
+            .iter()
+            .rev()
+            .fold(Fp::ZERO, |acc, &coefficient| acc * x + coefficient)
+    }
+
+    #[test]
+    fn karatsuba_matches_schoolbook() {
+        let a: [Fp; 33] = core::array::from_fn(|i| element(i as u64 * 7919 + 3));
+        let b: [Fp; 30] = core::array::from_fn(|i| element(i as u64 * 104_729 + 11));
+        let mut expected = [Fp::ZERO; 62];
+        schoolbook(&a, &b, &mut expected);
+        let mut product = [Fp::ZERO; 62];
+        mul(&a, &b, &mut product);
+        assert_eq!(product, expected);
+    }
+
+    #[test]
+    fn remainder_tree_evaluates() {
+        for leaves in [1, 2, 3, 7, 20, 32] {
+            let roots: [[Fp; 2]; MAX_LEAVES] =
+                core::array::from_fn(|i| [element(i as u64 * 31 + 5), Fp::ONE]);
+            let roots = &roots[..leaves];
+            let polynomial: [Fp; 41] = core::array::from_fn(|i| element(i as u64 * 13 + 1));
+
+            let tree = ProductTree::new(roots);
+            let expected = roots
+                .iter()
+                .fold(Fp::ONE, |acc, &[root, _]| acc * evaluate(&polynomial, root));
+            assert_eq!(tree.product_of_evaluations(&polynomial), expected);
+        }
+    }
+
+    #[test]
+    fn remainder_tree_evaluates_projectively() {
+        let roots: [[Fp; 2]; 13] =
+            core::array::from_fn(|i| [element(i as u64 * 31 + 5), element(i as u64 * 17 + 2)]);
+        let first: [Fp; 27] = core::array::from_fn(|i| element(i as u64 * 13 + 1));
+        let second: [Fp; 27] = core::array::from_fn(|i| element(i as u64 * i as u64 + 7));
+
+        // The evaluations are only known up to the same factor for both polynomials.
+        let tree = ProductTree::new(&roots);
+        let expected = |polynomial: &[Fp]| {
+            roots.iter().fold(Fp::ONE, |acc, &[x, z]| {
+                acc * evaluate(polynomial, x * z.inv().unwrap())
+            })
+        };
+        assert_eq!(
+            tree.product_of_evaluations(&first) * expected(&second),
+            tree.product_of_evaluations(&second) * expected(&first)
+        );
+    }
+
+    #[test]
+    fn product_of_quadratics() {
+        let factors: [[Fp; 3]; 21] = core::array::from_fn(|i| {
+            [
+                element(i as u64 + 2),
+                element(3 * i as u64 + 1),
+                element(i as u64 * i as u64 + 5),
+            ]
+        });
+        let mut product = [Fp::ZERO; 43];
+        super::product_of_quadratics(&factors, &mut product);
+
+        let x = element(123_456_789);
+        let expected = factors
+            .iter()
+            .fold(Fp::ONE, |acc, factor| acc * evaluate(factor, x));
+        assert_eq!(evaluate(&product, x), expected);
+    }
+}
@@ -0,0 +1,179 @@
+//! Square-root Vélu formulas, from <i>
+//! <a href=https://eprint.iacr.org/2020/341>
+//! Bernstein, D.J., De Feo, L., Leroux, A., Smith, B.: Faster computation of isogenies of large
+//! prime degree. In: ANTS XIV, Open Book Series 4. pp. 39–55. MSP (2020)</a></i>.
+//!
+//! Both the codomain and the images of points are derived from the polynomial
+//! h(X) = ∏ (X - x([s]P)) for s in S = {1, 3, 5, ..., ℓ - 2}. Instead of computing its
+//! (ℓ - 1) / 2 factors one by one, S is split into I ± J and K, with I = {2b(2i + 1)} of size b',
+//! J = {2j + 1} of size b, and K the remaining odd numbers below ℓ. Then, up to a factor that does
+//! not depend on X, h(X) is h<sub>K</sub>(X) times the resultant of h<sub>I</sub> and
+//! E<sub>J</sub>(X), where E<sub>J</sub> is a product of quadratic polynomials given by the
+//! biquadratic relation between x(P), x(Q), x(P + Q) and x(P - Q). Only O(√ℓ) multiples of P are
+//! computed.
+//!
+//! All the polynomials are evaluated projectively, at (X : Z), and built from projective points,
+//! so that no inversion is needed: the constant factors this introduces cancel out in the final
+//! formulas.
+
+use crypto_bigint::U64;
+
+use super::polynomial::{self, CAPACITY, MAX_LEAVES, ProductTree};
+use crate::{
+    csidh_params::Params, field::Field, montgomery_curve::MontgomeryCurve,
+    montgomery_point::MontgomeryPoint,
+};
+
+/// Precomputed data for the evaluation of an isogeny with the square-root Vélu formulas.
+pub struct SqrtVelu<P: Params, F: Field> {
+    domain: MontgomeryCurve<P, F>,
+    degree: u64,
+    /// Product tree of h<sub>I</sub>.
+    tree: ProductTree<F>,
+    /// (X, Z, CXZ, C(X^2 + Z^2) + 2AXZ) for each multiple [j]P, j in J.
+    baby_steps: [[F; 4]; MAX_LEAVES],
+    baby_steps_len: usize,
+    /// C, the denominator of the curve coefficient.
+    c: F,
+    /// (X, Z) for each multiple [k]P, k in K.
+    remaining: [[F; 2]; 2 * MAX_LEAVES],
+    remaining_len: usize,
+}
+
+impl<P: Params, F: Field> SqrtVelu<P, F> {
+    /// Precomputes the isogeny of odd prime degree `degree` from `domain` whose kernel is
+    /// generated by `kernel`.
+    ///
+    /// Returns `None` if the degree is too small or too large for the formulas.
+    pub fn new(
+        domain: &MontgomeryCurve<P, F>,
+        kernel: &MontgomeryPoint<P, F>,
+        degree: u64,
+    ) -> Option<Self> {
+        let b = ((degree - 1).isqrt() / 2) as usize;
+        if b == 0 || b > MAX_LEAVES {
+            return None;
+        }
+        let b_prime = ((degree - 1) / (4 * b as u64)) as usize;
+        if b_prime > MAX_LEAVES {
+            return None;
+        }
+        let k_len = ((degree - 1) / 2) as usize - 2 * b * b_prime;
+
+        let (a, c) = domain.a_c();
+        let double = kernel.double();
+
+        // J: odd multiples [1]P, [3]P, ..., [2b - 1]P.
+        let mut baby_steps = core::array::from_fn(|_| polynomial::zeros(&c));
+        let mut previous = kernel.clone();
+        let mut current = kernel.clone();
+        for step in baby_steps.iter_mut().take(b) {
+            let x = current.X();
+            let z = current.Z();
+            let xz = x.clone() * z;
+            *step = [
+                x.clone(),
+                z.clone(),
+                c.clone() * &xz,
+                c.clone() * (x.square() + z.square()) + a.double() * xz,
+            ];
+            // [j + 2]P = [j]P + [2]P, of difference [j - 2]P, which is -[1]P when j = 1.
+            let next = current.differential_add(&double, &previous);
+            previous = core::mem::replace(&mut current, next);
+        }
+
+        // I: multiples [2b]P, [6b]P, ..., [2b(2b' - 1)]P.
+        let mut giant_steps: [[F; 2]; MAX_LEAVES] = core::array::from_fn(|_| polynomial::zeros(&c));
+        let first = kernel.mul_vartime(&U64::from_u64(2 * b as u64));
+        let stride = first.double();
+        let mut previous = first.clone();
+        let mut current = first;
+        for step in giant_steps.iter_mut().take(b_prime) {
+            *step = [current.X().clone(), current.Z().clone()];
+            let next = current.differential_add(&stride, &previous);
+            previous = core::mem::replace(&mut current, next);
+        }
+        let tree = ProductTree::new(&giant_steps[..b_prime]);
+
+        // K: multiples [4bb' + 1]P, [4bb' + 3]P, ..., [ℓ - 2]P.
+        let mut remaining = core::array::from_fn(|_| polynomial::zeros(&c));
+        let start = 4 * (b * b_prime) as u64 + 1;
+        let mut previous = kernel.mul_vartime(&U64::from_u64(start - 2));
+        let mut current = kernel.mul_vartime(&U64::from_u64(start));
+        for step in remaining.iter_mut().take(k_len) {
+            *step = [current.X().clone(), current.Z().clone()];
+            let next = current.differential_add(&double, &previous);
+            previous = core::mem::replace(&mut current, next);
+        }
+
+        Some(Self {
+            domain: domain.clone(),
+            degree,
+            tree,
+            baby_steps,
+            baby_steps_len: b,
+            c,
+            remaining,
+            remaining_len: k_len,
+        })
+    }
+
+    /// Evaluates the homogenized h at (X : Z), up to a factor independent of (X : Z).
+    fn evaluate(&self, x: &F, z: &F) -> F {
+        let two = F::from_u64_like(2, x);
+        let x_square = x.square();
+        let z_square = z.square();
+        let xz = x.clone() * z;
+
+        // For each j in J, F0(W, x_j) X^2 + F1(W, x_j) XZ + F2(W, x_j) Z^2 as a polynomial in W,
+        // whose roots are x([i + j]P) and x([i - j]P) when evaluated at W = x([i]P). Every factor
+        // is multiplied by C so that the curve coefficient needs no inversion.
+        let mut factors: [[F; 3]; MAX_LEAVES] = core::array::from_fn(|_| polynomial::zeros(x));
+        for (factor, [xj, zj, c_xjzj, sum]) in factors
+            .iter_mut()
+            .zip(&self.baby_steps[..self.baby_steps_len])
+        {
+            *factor = [
+                self.c.clone() * (x.clone() * xj - z.clone() * zj).square(),
+                -(two.clone()
+                    * (c_xjzj.clone() * (x_square.clone() + &z_square) + sum.clone() * &xz)),
+                self.c.clone() * (x.clone() * zj - z.clone() * xj).square(),
+            ];
+        }
+        let mut e_j: [F; CAPACITY] = polynomial::zeros(x);
+        let e_j = &mut e_j[..2 * self.baby_steps_len + 1];
+        polynomial::product_of_quadratics(&factors[..self.baby_steps_len], e_j);
+
+        let mut result = self.tree.product_of_evaluations(e_j);
+        for [xk, zk] in &self.remaining[..self.remaining_len] {
+            result *= x.clone() * zk - z.clone() * xk;
+        }
+        result
+    }
+
+    /// Computes the codomain of the isogeny.
+    ///
+    /// With the notations of [`Isogeny::codomain`](super::Isogeny::codomain), the products of
+    /// X<sub>i</sub> + Z<sub>i</sub> and X<sub>i</sub> - Z<sub>i</sub> are h(-1) and h(1) up to
+    /// the same factor.
+    pub fn codomain(&self) -> MontgomeryCurve<P, F> {
+        let one = F::one_like(&self.c);
+        let plus = self.evaluate(&-one.clone(), &one);
+        let minus = self.evaluate(&one, &one);
+        super::codomain(&self.domain, self.degree, plus, minus)
+    }
+
+    /// Computes the image of `point` on `codomain`: (X : Z) is sent to
+    /// (X h(Z : X)^2 : Z h(X : Z)^2).
+    pub fn image(
+        &self,
+        codomain: &MontgomeryCurve<P, F>,
+        point: &MontgomeryPoint<P, F>,
+    ) -> MontgomeryPoint<P, F> {
+        let x = point.X();
+        let z = point.Z();
+        let x_prime = x.clone() * self.evaluate(z, x).square();
+        let z_prime = z.clone() * self.evaluate(x, z).square();
+        MontgomeryPoint::new(codomain.clone(), x_prime, z_prime)
+    }
+}
@@ -0,0 +1,60 @@
+use crypto_bigint::{modular::ConstMontyParams, rand_core::CryptoRngCore};
+
+use crate::{csidh_params::CsidhParams, private_key::PrivateKey, public_key::PublicKey};
+
+/// A private key along with its associated public key.
+#[derive(Clone, Debug)]
+pub struct KeyPair<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>> {
+    private_key: PrivateKey<LIMBS, N, MOD>,
+    public_key: PublicKey<LIMBS, MOD>,
+}
+
+impl<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>> KeyPair<LIMBS, N, MOD> {
+    /// Generates a random private key and computes its associated public key.
+    #[must_use]
+    pub fn generate(params: CsidhParams<LIMBS, N, MOD>, rng: &mut impl CryptoRngCore) -> Self {
+        let private_key = PrivateKey::random(params, rng);
+        Self::from(private_key, rng)
+    }
+
+    /// Computes the public key associated with the given private key and bundles them together.
+    #[must_use]
+    pub fn from(private_key: PrivateKey<LIMBS, N, MOD>, rng: &mut impl CryptoRngCore) -> Self {
+        let public_key = PublicKey::from(&private_key, rng);
+        Self {
+            private_key,
+            public_key,
+        }
+    }
+
+    /// Returns the private key of the pair.
+    #[must_use]
+    pub const fn private_key(&self) -> &PrivateKey<LIMBS, N, MOD> {
+        &self.private_key
+    }
+
+    /// Returns the public key of the pair.
+    #[must_use]
+    pub const fn public_key(&self) -> PublicKey<LIMBS, MOD> {
+        self.public_key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::csidh_params::toy;
+
+    use super::*;
+
+    #[test]
+    fn generated_public_key_matches_private_key() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10 {
+            let key_pair = KeyPair::generate(toy::TOY, &mut rng);
+            assert_eq!(
+                key_pair.public_key(),
+                PublicKey::from(key_pair.private_key(), &mut rng)
+            );
+        }
+    }
+}
@@ -24,45 +24,75 @@
 //! const ALICE_PRIVATE_KEY: PrivateKeyCsidh512 = PrivateKey::new(
 //!     CsidhParams::CSIDH_512,
 //!     [
-//!         8, 2, 9, 3, 3, 0, 7, 2, 0, 8, 1, 9, 9, 4, 0, 10, 6, 3, 10, 7, 2, 3, 1, 4, 5, 3, 9, 10,
-//!         9, 3, 8, 5, 1, 10, 2, 4, 2, 10, 1, 1, 10, 8, 0, 9, 1, 8, 7, 6, 10, 9, 9, 4, 10, 6, 4,
-//!         4, 2, 3, 5, 5, 5, 3, 0, 9, 6, 9, 8, 5, 5, 9, 2, 0, 3, 6,
+//!         3, -3, 4, -2, -2, -5, 2, -3, -5, 3, -4, 4, 4, -1, -5, 5, 1, -2, 5, 2, -3, -2, -4, -1, 0,
+//!         -2, 4, 5, 4, -2, 3, 0, -4, 5, -3, -1, -3, 5, -4, -4, 5, 3, -5, 4, -4, 3, 2, 1, 5, 4, 4,
+//!         -1, 5, 1, -1, -1, -3, -2, 0, 0, 0, -2, -5, 4, 1, 4, 3, 0, 0, 4, -3, -5, -2, 1,
 //!     ],
 //! );
-//! let alice_public_key = PublicKey::from(ALICE_PRIVATE_KEY, &mut rng);
+//! let alice_public_key = PublicKey::from(&ALICE_PRIVATE_KEY, &mut rng);
 //!
 //! // Bob
 //! const BOB_PRIVATE_KEY: PrivateKeyCsidh512 = PrivateKey::new(
 //!     CsidhParams::CSIDH_512,
 //!     [
-//!         1, 2, 0, 6, 2, 1, 2, 6, 4, 3, 10, 1, 4, 0, 1, 7, 5, 6, 9, 10, 8, 9, 7, 5, 4, 7, 10, 10,
-//!         5, 6, 5, 2, 1, 4, 0, 6, 0, 3, 8, 7, 0, 10, 0, 3, 0, 3, 6, 9, 2, 3, 4, 4, 3, 3, 0, 10,
-//!         10, 2, 1, 4, 8, 10, 6, 0, 7, 1, 2, 7, 2, 0, 9, 9, 0, 6,
+//!         -4, -3, -5, 1, -3, -4, -3, 1, -1, -2, 5, -4, -1, -5, -4, 2, 0, 1, 4, 5, 3, 4, 2, 0, -1,
+//!         2, 5, 5, 0, 1, 0, -3, -4, -1, -5, 1, -5, -2, 3, 2, -5, 5, -5, -2, -5, -2, 1, 4, -3, -2,
+//!         -1, -1, -2, -2, -5, 5, 5, -3, -4, -1, 3, 5, 1, -5, 2, -4, -3, 2, -3, -5, 4, 4, -5, 1,
 //!     ],
 //! );
-//! let bob_public_key = PublicKey::from(BOB_PRIVATE_KEY, &mut rng);
+//! let bob_public_key = PublicKey::from(&BOB_PRIVATE_KEY, &mut rng);
 //!
 //! // Shared secret
-//! let alice_shared_secret = SharedSecret::from(bob_public_key, ALICE_PRIVATE_KEY, &mut rng);
-//! let bob_shared_secret = SharedSecret::from(alice_public_key, BOB_PRIVATE_KEY, &mut rng);
+//! let alice_shared_secret = SharedSecret::from(bob_public_key, &ALICE_PRIVATE_KEY, &mut rng);
+//! let bob_shared_secret = SharedSecret::from(alice_public_key, &BOB_PRIVATE_KEY, &mut rng);
 //! assert_eq!(alice_shared_secret, bob_shared_secret);
 //! ```
 
 #![no_std]
 #![warn(missing_docs, missing_debug_implementations)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
 mod csidh;
 mod csidh_params;
+#[cfg(feature = "alloc")]
+mod dynamic;
+mod error;
+mod field;
+mod isogeny;
+mod key_pair;
 mod montgomery_curve;
 mod montgomery_point;
 mod private_key;
 mod public_key;
 mod shared_secret;
+mod strategy;
 
 #[doc(no_inline)]
 pub use crypto_bigint::{Uint, impl_modulus, modular::ConstMontyForm};
 
-pub use csidh_params::CsidhParams;
-pub use private_key::{PrivateKey, PrivateKeyCsidh512, PrivateKeyCsidh1024, PrivateKeyCsidh1792};
-pub use public_key::PublicKey;
+pub use csidh_params::{CsidhParams, EvaluationMode};
+#[cfg(feature = "std")]
+pub use dynamic::PrimeSearch;
+#[cfg(feature = "alloc")]
+pub use dynamic::{DynCsidhParams, DynPrivateKey, DynPublicKey};
+pub use error::Error;
+pub use field::{AdditionChain, AdditionChains, Step};
+pub use key_pair::KeyPair;
+pub use private_key::{
+    PrivateKey, PrivateKeyCsidh512, PrivateKeyCsidh1024, PrivateKeyCsidh1792, PrivateKeyFormat,
+};
+pub use public_key::{PublicKey, SupersingularityCertificate};
 pub use shared_secret::SharedSecret;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::csidh_params::macros::*;
+    pub use crypto_bigint::{
+        Limb, Odd,
+        modular::{ConstMontyParams, montgomery_reduction},
+    };
+}
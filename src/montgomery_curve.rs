@@ -1,80 +1,460 @@
-use crypto_bigint::{
-    Random, Uint,
-    modular::{ConstMontyForm, ConstMontyParams},
-    rand_core::CryptoRngCore,
+use crypto_bigint::{BitOps, U64, WrappingMul, rand_core::CryptoRngCore};
+
+use crate::{
+    csidh_params::Params,
+    field::{self, Field},
+    montgomery_point::MontgomeryPoint,
 };
 
-use crate::{CsidhParams, montgomery_point::MontgomeryPoint};
+/// Values of u used by [`MontgomeryCurve::elligator`], none of them being 0, 1 or -1.
+const ELLIGATOR_TABLE: [u64; 32] = [
+    2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27,
+    28, 29, 30, 31, 32, 33,
+];
+
+/// Number of curves verified together by [`MontgomeryCurve::are_supersingular`].
+pub const BATCH_SIZE: usize = 16;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct MontgomeryCurve<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>> {
-    params: CsidhParams<LIMBS, N, MOD>,
-    a2: ConstMontyForm<MOD, LIMBS>,
-    a24: ConstMontyForm<MOD, LIMBS>,
+/// Montgomery curve By^2 = x^3 + Ax^2 + x, or (C)By^2 = C x^3 + Ax^2 + Cx projectively.
+///
+/// The curve is stored as (A + 2C : 4C), the form used by the doubling formulas, so that
+/// isogenies can compute their codomain without inversions.
+#[derive(Debug, Clone)]
+pub struct MontgomeryCurve<P: Params, F: Field = <P as Params>::Element> {
+    params: P,
+    a24_plus: F,
+    c24: F,
 }
 
-impl<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>>
-    MontgomeryCurve<LIMBS, N, MOD>
-{
-    pub const fn a2(&self) -> ConstMontyForm<MOD, LIMBS> {
-        self.a2
+impl<P: Params, F: Field> MontgomeryCurve<P, F> {
+    pub const fn params(&self) -> P {
+        self.params
+    }
+
+    /// Returns A + 2C.
+    pub const fn a24_plus(&self) -> &F {
+        &self.a24_plus
+    }
+
+    /// Returns 4C.
+    pub const fn c24(&self) -> &F {
+        &self.c24
     }
 
-    pub const fn a24(&self) -> ConstMontyForm<MOD, LIMBS> {
-        self.a24
+    /// Returns (A, C), up to a common factor.
+    pub fn a_c(&self) -> (F, F) {
+        let a24_plus_2 = self.a24_plus.double();
+        (a24_plus_2.double() - self.c24.double(), self.c24.clone())
     }
 
-    pub const fn new(params: CsidhParams<LIMBS, N, MOD>, a2: ConstMontyForm<MOD, LIMBS>) -> Self {
-        let two = ConstMontyForm::new(&Uint::from_u32(2));
-        let inverse_of_4 = params.inverse_of_4();
-        // TODO use operators once feature(const_trait_impl) is stabilized
-        let a24 = (a2.add(&two)).mul(&inverse_of_4);
-        Self { params, a2, a24 }
+    /// Constructs the curve of affine coefficient `a2`, as ((A + 2) / 4 : 1).
+    pub fn new(params: P, a2: F) -> Self {
+        let inverse_of_4 = F::from_montgomery_words(params.inverse_of_4().montgomery_words(), &a2);
+        let two = F::from_u64_like(2, &a2);
+        let c24 = F::one_like(&a2);
+        Self {
+            params,
+            a24_plus: (a2 + two) * inverse_of_4,
+            c24,
+        }
+    }
+
+    /// Constructs the curve of projective coefficients (A + 2C : 4C).
+    pub const fn new_projective(params: P, a24_plus: F, c24: F) -> Self {
+        Self {
+            params,
+            a24_plus,
+            c24,
+        }
     }
 
-    pub fn lift(&self, x: ConstMontyForm<MOD, LIMBS>) -> Option<MontgomeryPoint<LIMBS, N, MOD>> {
+    /// Returns the Legendre symbol of x^3 + Ax^2 + x, in variable time.
+    ///
+    /// It is computed as the one of C^2 (x^3 + (A / C) x^2 + x), which needs no inversion.
+    fn legendre_vartime(&self, x: &F) -> i8 {
+        let (a, c) = self.a_c();
         let x_square = x.square();
-        let n = x * x_square + self.a2 * x_square + x;
-        if n.pow(&self.params.p_minus_1_over_2()) == ConstMontyForm::ONE {
-            Some(MontgomeryPoint::new_reduced(*self, x))
+        (c.clone() * (c * (x_square.clone() * x + x) + a * x_square)).legendre_vartime()
+    }
+
+    pub fn lift(&self, x: F) -> Option<MontgomeryPoint<P, F>> {
+        if self.legendre_vartime(&x) == 1 {
+            Some(MontgomeryPoint::new_reduced(self.clone(), x))
         } else {
             None
         }
     }
 
-    pub fn random_point(&self, rng: &mut impl CryptoRngCore) -> MontgomeryPoint<LIMBS, N, MOD> {
+    /// Returns a point on the curve and a point on its quadratic twist, in constant time, with the
+    /// Elligator 2 map of <i><a href=https://eprint.iacr.org/2013/325>
+    /// Bernstein, D.J., Hamburg, M., Krasnova, A., Lange, T.: Elligator: elliptic-curve points
+    /// indistinguishable from uniform random strings. In: CCS 2013. pp. 967–980. ACM
+    /// (2013)</a></i>.
+    ///
+    /// Since p = 3 mod 4, exactly one of x = A / (C(u^2 - 1)) and -x - A / C is the x-coordinate of
+    /// a point on the curve, the other one being on the twist. When A = 0, u and -u are used
+    /// instead. `u` must not be 0, 1 or -1. Twist points are represented on this curve, since the
+    /// x-only arithmetic is the same on both.
+    pub fn elligator(&self, u: F) -> (MontgomeryPoint<P, F>, MontgomeryPoint<P, F>) {
+        let (a, c) = self.a_c();
+        let one = F::one_like(&u);
+        let u_square = u.square();
+        let a_is_zero = a.ct_eq(&F::zero_like(&u));
+
+        let x = F::ct_select(&a, &u, a_is_zero);
+        let other_x = F::ct_select(&-(a.clone() * &u_square), &-u, a_is_zero);
+        let z = F::ct_select(&(c.clone() * (u_square - &one)), &one, a_is_zero);
+
+        // The Legendre symbol of f(X / Z) is the one of C^2 Z^4 f(X / Z) = CXZ(CX^2 + AXZ + CZ^2).
+        let xz = x.clone() * &z;
+        let n = c.clone() * &xz * (c * (x.square() + z.square()) + a * xz);
+        let on_twist = !n.is_square();
+
+        let mut point = MontgomeryPoint::new(self.clone(), x, z.clone());
+        let mut twist_point = MontgomeryPoint::new(self.clone(), other_x, z);
+        MontgomeryPoint::conditional_swap(&mut point, &mut twist_point, on_twist);
+        (point, twist_point)
+    }
+
+    /// Returns a point on the curve and a point on its quadratic twist, from a random entry of a
+    /// precomputed table of Elligator inputs.
+    pub fn random_elligator_points(
+        &self,
+        rng: &mut impl CryptoRngCore,
+    ) -> (MontgomeryPoint<P, F>, MontgomeryPoint<P, F>) {
+        let index = rng.next_u32() as usize % ELLIGATOR_TABLE.len();
+        self.elligator(F::from_u64_like(ELLIGATOR_TABLE[index], &self.c24))
+    }
+
+    pub fn random_point(&self, rng: &mut impl CryptoRngCore) -> MontgomeryPoint<P, F> {
         loop {
-            let x = ConstMontyForm::new(&Uint::random(rng));
+            let x = F::random_like(&self.c24, rng);
             if let Some(point) = self.lift(x) {
                 return point;
             }
         }
     }
 
+    /// Returns the affine coefficient A of the curve, which costs an inversion.
+    pub fn a2(&self) -> F {
+        let (a, c) = self.a_c();
+        a * field::inv(&self.params, &c)
+    }
+
+    /// Returns whether the curve is supersingular, following the verification algorithm of
+    /// <i><a href=https://eprint.iacr.org/2018/383>
+    /// Castryck, W., Lange, T., Martindale, C., Panny, L., Renes, J.: CSIDH: an efficient
+    /// post-quantum commutative group action. In: ASIACRYPT 2018, LNCS 11274. pp. 395–427.
+    /// Springer (2018)</a></i>, with the orders of the prime-order multiples of a random point
+    /// computed along a product tree.
+    ///
+    /// An attempt proves the curve supersingular as soon as the known divisors of the order of the
+    /// point exceed 4√p, and ordinary as soon as a multiple of the point is not killed by its
+    /// prime. Otherwise, another point is tried, and the curve is rejected after
+    /// [`CsidhParams::validation_attempts`](crate::CsidhParams::validation_attempts) attempts.
     pub fn is_supersingular(&self, rng: &mut impl CryptoRngCore) -> bool {
-        let point = self.random_point(rng);
-        let mut d = Uint::<LIMBS>::ONE;
-        let sqrt_of_p_times_4 = self.params.sqrt_of_p_times_4();
-
-        for li in self.params.lis() {
-            let mut value = Uint::from(4u32);
-            for li_2 in self.params.lis() {
-                if li_2 != li {
-                    value = value.wrapping_mul(&Uint::<1>::from(li_2));
+        self.certify(rng).is_some()
+    }
+
+    /// Returns whether each of `curves` is supersingular like [`MontgomeryCurve::is_supersingular`],
+    /// with the product tree `tree` of the primes, `false` standing for the missing curves.
+    ///
+    /// The curves must have been constructed by [`MontgomeryCurve::new`]. They are verified in
+    /// lockstep: each attempt draws the points of all the undecided curves at once, and their
+    /// multiples by the same products of primes are reduced together with a single inversion, when
+    /// the cheaper ladder steps it enables pay for it.
+    ///
+    /// As in the CSIDH paper, the points are not lifted to the curves: random x-coordinates may
+    /// belong to their quadratic twists, which are supersingular exactly when the curves are, so
+    /// that no Legendre symbol has to be computed.
+    pub fn are_supersingular(
+        curves: &[Option<Self>; BATCH_SIZE],
+        tree: &ProductTree<P>,
+        rng: &mut impl CryptoRngCore,
+    ) -> [bool; BATCH_SIZE] {
+        let mut verdicts = curves
+            .each_ref()
+            .map(|curve| curve.is_none().then_some(false));
+        let Some(params) = curves.iter().flatten().next().map(Self::params) else {
+            return [false; BATCH_SIZE];
+        };
+        let four = U64::from_u64(4);
+
+        for _ in 0..params.validation_attempts() {
+            if verdicts.iter().all(Option::is_some) {
+                break;
+            }
+            let points = core::array::from_fn(|index| match (&curves[index], verdicts[index]) {
+                (Some(curve), None) => {
+                    let x = F::random_like(&curve.c24, rng);
+                    Some(MontgomeryPoint::new_reduced(curve.clone(), x).mul_vartime_affine(&four))
                 }
+                _ => None,
+            });
+            let mut orders = core::array::from_fn(|_| params.integer(1));
+            let mut attempt = [None; BATCH_SIZE];
+            Self::divide_orders(
+                &params,
+                tree,
+                points,
+                (0, params.lis().len()),
+                &mut orders,
+                &mut attempt,
+            );
+            for (verdict, attempt) in verdicts.iter_mut().zip(attempt) {
+                *verdict = verdict.or(attempt);
             }
+        }
+        verdicts.map(|verdict| verdict == Some(true))
+    }
+
+    /// Returns the x-coordinate of a point P proving that the curve is supersingular, with the
+    /// divisor d of the order of \[4\]P found by [`MontgomeryCurve::is_supersingular`], d being
+    /// greater than 4√p, or `None` if the curve is rejected.
+    pub fn certify(&self, rng: &mut impl CryptoRngCore) -> Option<(F, P::Integer)> {
+        self.certify_with(&ProductTree::new(&self.params), rng)
+    }
 
-            let qi = point * value;
-            if !(qi * Uint::from(li)).is_infinity() {
-                return false;
+    /// Returns whether the point of x-coordinate `x` proves that the curve is supersingular, with
+    /// the divisor `order` of the order of 4 times the point, as returned by
+    /// [`MontgomeryCurve::certify`].
+    pub fn check_certificate(&self, x: F, order: &P::Integer) -> bool {
+        self.lift(x).is_some_and(|point| {
+            self.verify(&ProductTree::new(&self.params), point)
+                == Verdict::Supersingular(order.clone())
+        })
+    }
+
+    /// Runs [`MontgomeryCurve::certify`] with the product tree `tree` of the primes.
+    fn certify_with(
+        &self,
+        tree: &ProductTree<P>,
+        rng: &mut impl CryptoRngCore,
+    ) -> Option<(F, P::Integer)> {
+        for _ in 0..self.params.validation_attempts() {
+            let point = self.random_point(rng);
+            let x = point.X().clone();
+            match self.verify(tree, point) {
+                Verdict::Supersingular(order) => return Some((x, order)),
+                Verdict::Ordinary => return None,
+                Verdict::Inconclusive => {}
             }
-            if !qi.is_infinity() {
-                d = d.wrapping_mul(&Uint::<1>::from(li));
+        }
+        None
+    }
+
+    /// Runs one attempt of [`MontgomeryCurve::is_supersingular`] with the point `point`.
+    fn verify(&self, tree: &ProductTree<P>, point: MontgomeryPoint<P, F>) -> Verdict<P::Integer> {
+        let point = point.mul_vartime(&U64::from_u64(4));
+        let mut order = self.params.integer(1);
+        match self.divide_order(tree, point, 0, self.params.lis().len(), &mut order) {
+            Some(true) => Verdict::Supersingular(order),
+            Some(false) => Verdict::Ordinary,
+            None => Verdict::Inconclusive,
+        }
+    }
+
+    /// Multiplies `order` by the primes of `lis[start..end]` dividing the order of `point`, which
+    /// must divide their product on a supersingular curve.
+    ///
+    /// `point` is multiplied by the product of either half of the primes to get the points of the
+    /// other half, so that each prime is isolated in O(log N) scalar multiplications. Returns
+    /// whether the curve is supersingular as soon as it is known, or `None`.
+    fn divide_order(
+        &self,
+        tree: &ProductTree<P>,
+        point: MontgomeryPoint<P, F>,
+        start: usize,
+        end: usize,
+        order: &mut P::Integer,
+    ) -> Option<bool> {
+        if point.is_infinity() {
+            return None;
+        }
+        if end - start == 1 {
+            let li = self.params.lis()[start];
+            if !point.mul_vartime(&U64::from_u64(li)).is_infinity() {
+                return Some(false);
             }
-            if d > sqrt_of_p_times_4 {
-                return true;
+            *order = order.wrapping_mul(&self.params.integer(li));
+            return (*order > *self.params.sqrt_of_p_times_4()).then_some(true);
+        }
+
+        let middle = start + (end - start) / 2;
+        let left_point = point.mul_vartime(&tree.right.as_ref()[middle]);
+        let right_point = point.mul_vartime(&tree.left.as_ref()[middle]);
+        self.divide_order(tree, left_point, start, middle, order)
+            .or_else(|| self.divide_order(tree, right_point, middle, end, order))
+    }
+
+    /// Runs [`MontgomeryCurve::divide_order`] on the points of several curves at once, for
+    /// [`MontgomeryCurve::are_supersingular`], storing in `verdicts` whether each curve is
+    /// supersingular as soon as it is known.
+    ///
+    /// The traversal of the product tree is the same as for a single point, a point leaving it when
+    /// it reaches infinity or its curve is decided.
+    fn divide_orders(
+        params: &P,
+        tree: &ProductTree<P>,
+        mut points: [Option<MontgomeryPoint<P, F>>; BATCH_SIZE],
+        (start, end): (usize, usize),
+        orders: &mut [P::Integer; BATCH_SIZE],
+        verdicts: &mut [Option<bool>; BATCH_SIZE],
+    ) {
+        for point in &mut points {
+            if point.as_ref().is_some_and(MontgomeryPoint::is_infinity) {
+                *point = None;
+            }
+        }
+        let alive = points.iter().flatten().count();
+        if alive == 0 {
+            return;
+        }
+
+        if end - start == 1 {
+            let li = params.lis()[start];
+            let points = points
+                .iter()
+                .zip(orders.iter_mut())
+                .zip(verdicts.iter_mut());
+            for ((point, order), verdict) in points {
+                let Some(point) = point else { continue };
+                if !point.mul_vartime_affine(&U64::from_u64(li)).is_infinity() {
+                    *verdict = Some(false);
+                    continue;
+                }
+                *order = order.wrapping_mul(&params.integer(li));
+                if *order > *params.sqrt_of_p_times_4() {
+                    *verdict = Some(true);
+                }
             }
+            return;
+        }
+
+        let middle = start + (end - start) / 2;
+        let left_scalar = &tree.right.as_ref()[middle];
+        let right_scalar = &tree.left.as_ref()[middle];
+        // An inversion costs about as many multiplications as p has bits, and reducing the points
+        // saves one multiplication per ladder step.
+        let steps = alive as u32 * (left_scalar.bits_vartime() + right_scalar.bits_vartime());
+        if steps > params.p().bits_vartime() {
+            MontgomeryPoint::reduce_all(&mut points);
+        }
+        let left_points = points.each_ref().map(|point| {
+            point
+                .as_ref()
+                .map(|point| point.mul_vartime_affine(left_scalar))
+        });
+        let mut right_points = points.each_ref().map(|point| {
+            point
+                .as_ref()
+                .map(|point| point.mul_vartime_affine(right_scalar))
+        });
+
+        Self::divide_orders(params, tree, left_points, (start, middle), orders, verdicts);
+        for (point, verdict) in right_points.iter_mut().zip(verdicts.iter()) {
+            if verdict.is_some() {
+                *point = None;
+            }
+        }
+        Self::divide_orders(params, tree, right_points, (middle, end), orders, verdicts);
+    }
+}
+
+/// Result of an attempt of [`MontgomeryCurve::is_supersingular`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Verdict<I> {
+    /// The curve is supersingular, as the order of the point has this divisor, greater than 4√p.
+    Supersingular(I),
+    /// The curve is ordinary, as the order of the point does not divide p + 1.
+    Ordinary,
+    /// The order of the point is too small to conclude.
+    Inconclusive,
+}
+
+/// Products of the halves of the primes, as split by [`MontgomeryCurve::divide_order`].
+///
+/// Each range of primes is split at a different index, where the products of both halves are
+/// stored, so that the tree fits in two arrays of N elements.
+pub struct ProductTree<P: Params> {
+    left: P::Array<P::Integer>,
+    right: P::Array<P::Integer>,
+}
+
+impl<P: Params> ProductTree<P> {
+    pub fn new(params: &P) -> Self {
+        let mut tree = Self {
+            left: params.array(params.integer(1)),
+            right: params.array(params.integer(1)),
+        };
+        let len = params.lis().len();
+        if len > 0 {
+            tree.fill(params, 0, len);
+        }
+        tree
+    }
+
+    /// Stores the products of the splits of `lis[start..end]`, and returns the product of the
+    /// whole range.
+    fn fill(&mut self, params: &P, start: usize, end: usize) -> P::Integer {
+        if end - start == 1 {
+            return params.integer(params.lis()[start]);
+        }
+        let middle = start + (end - start) / 2;
+        let left = self.fill(params, start, middle);
+        let right = self.fill(params, middle, end);
+        let product = left.wrapping_mul(&right);
+        self.left.as_mut()[middle] = left;
+        self.right.as_mut()[middle] = right;
+        product
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crypto_bigint::{Uint, modular::ConstMontyForm};
+
+    use super::*;
+    use crate::{PrivateKey, csidh::csidh, csidh_params::toy};
+
+    #[test]
+    fn elligator_points_are_on_both_sides() {
+        let mut rng = rand::thread_rng();
+        let params = toy::TOY;
+        let other = csidh(
+            params,
+            &[1, -2, 0, 3, 1, 0, -1, 2, 0, 1, -1],
+            ConstMontyForm::ZERO,
+            &mut rng,
+        );
+        for a2 in [ConstMontyForm::ZERO, other] {
+            let curve = MontgomeryCurve::new(params, a2);
+            for u in ELLIGATOR_TABLE {
+                let (point, twist_point) = curve.elligator(ConstMontyForm::new(&Uint::from(u)));
+                let x = *point.X() * point.Z().inv().unwrap();
+                let twist_x = *twist_point.X() * twist_point.Z().inv().unwrap();
+                assert_eq!(curve.legendre_vartime(&x), 1);
+                assert_eq!(curve.legendre_vartime(&twist_x), -1);
+            }
+        }
+    }
+
+    #[test]
+    fn is_supersingular() {
+        let mut rng = rand::thread_rng();
+        let params = toy::TOY;
+        for _ in 0..4 {
+            let key = PrivateKey::random(params, &mut rng);
+            let a2 = csidh(params, key.key(), ConstMontyForm::ZERO, &mut rng);
+            assert!(MontgomeryCurve::new(params, a2).is_supersingular(&mut rng));
+        }
+        // A = 6 is supersingular too, with j-invariant 66^3, but the other small ones are not.
+        for a2 in [3, 4, 5, 7, 8, 9] {
+            let curve = MontgomeryCurve::new(
+                params,
+                ConstMontyForm::<toy::MOD, { toy::LIMBS }>::new(&Uint::from_u64(a2)),
+            );
+            assert!(!curve.is_supersingular(&mut rng));
         }
-        self.is_supersingular(rng)
     }
 }
@@ -1,198 +1,193 @@
 use core::ops::Mul;
 
 use crypto_bigint::{
-    ConstChoice, Odd, PrecomputeInverter, Uint,
-    modular::{ConstMontyForm, ConstMontyParams, SafeGcdInverter},
+    BitOps, Integer,
+    subtle::{Choice, ConstantTimeEq},
 };
 
-use crate::montgomery_curve::MontgomeryCurve;
+use crate::{
+    csidh_params::Params,
+    field::{self, Field},
+    montgomery_curve::MontgomeryCurve,
+};
 
-pub struct PointMultiples<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>> {
-    n_times_p: MontgomeryPoint<LIMBS, N, MOD>,
-    p: MontgomeryPoint<LIMBS, N, MOD>,
-    n_minus_1_times_p: MontgomeryPoint<LIMBS, N, MOD>,
-    left: Uint<LIMBS>,
+pub struct PointMultiples<P: Params, F: Field = <P as Params>::Element> {
+    n_times_p: MontgomeryPoint<P, F>,
+    p: MontgomeryPoint<P, F>,
+    n_minus_1_times_p: MontgomeryPoint<P, F>,
+    n: u64,
+    left: u64,
 }
 
-impl<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>>
-    PointMultiples<LIMBS, N, MOD>
-{
-    const fn new(p: MontgomeryPoint<LIMBS, N, MOD>, d: Uint<LIMBS>) -> Self {
+impl<P: Params, F: Field> PointMultiples<P, F> {
+    fn new(p: MontgomeryPoint<P, F>, d: u64) -> Self {
         Self {
-            n_times_p: MontgomeryPoint::infinity(p.curve),
+            n_times_p: MontgomeryPoint::infinity(p.curve.clone()),
+            n_minus_1_times_p: MontgomeryPoint::infinity(p.curve.clone()),
             p,
-            n_minus_1_times_p: MontgomeryPoint::infinity(p.curve),
+            n: 0,
             left: d,
         }
     }
 }
 
-impl<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>> Iterator
-    for PointMultiples<LIMBS, N, MOD>
-{
-    type Item = MontgomeryPoint<LIMBS, N, MOD>;
+impl<P: Params, F: Field> Iterator for PointMultiples<P, F> {
+    type Item = MontgomeryPoint<P, F>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.left == Uint::ZERO {
+        if self.left == 0 {
             return None;
         }
 
-        if self.n_times_p.is_infinity() {
-            self.n_times_p = self.p;
-            self.left -= Uint::ONE;
-            return Some(self.p);
-        }
-
-        let x1 = self.n_times_p.X;
-        let x2 = self.p.X;
-
-        let n_plus_1_times_p = if x1 == x2 {
-            self.p.double()
-        } else {
-            self.n_times_p
-                .differential_add(self.p, self.n_minus_1_times_p)
+        // The formula used only depends on the index of the multiple, never on the point
+        // itself: a differential addition cannot compute 2P as its difference is infinity.
+        let n_plus_1_times_p = match self.n {
+            0 => self.p.clone(),
+            1 => self.p.double(),
+            _ => self
+                .n_times_p
+                .differential_add(&self.p, &self.n_minus_1_times_p),
         };
 
-        self.n_minus_1_times_p = self.n_times_p;
-        self.n_times_p = n_plus_1_times_p;
-        self.left -= Uint::ONE;
+        self.n_minus_1_times_p = core::mem::replace(&mut self.n_times_p, n_plus_1_times_p.clone());
+        self.n += 1;
+        self.left -= 1;
         Some(n_plus_1_times_p)
     }
 }
 
 #[allow(non_snake_case)]
-#[derive(Debug, Clone, Copy)]
-pub struct MontgomeryPoint<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>> {
-    curve: MontgomeryCurve<LIMBS, N, MOD>,
-    X: ConstMontyForm<MOD, LIMBS>,
-    Z: ConstMontyForm<MOD, LIMBS>,
+#[derive(Debug, Clone)]
+pub struct MontgomeryPoint<P: Params, F: Field = <P as Params>::Element> {
+    curve: MontgomeryCurve<P, F>,
+    X: F,
+    Z: F,
 }
 
-impl<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>>
-    MontgomeryPoint<LIMBS, N, MOD>
-{
+impl<P: Params, F: Field> MontgomeryPoint<P, F> {
     #[allow(non_snake_case)]
-    pub const fn new_reduced(
-        curve: MontgomeryCurve<LIMBS, N, MOD>,
-        X: ConstMontyForm<MOD, LIMBS>,
-    ) -> Self {
-        Self {
-            curve,
-            X,
-            Z: ConstMontyForm::ONE,
-        }
+    pub fn new_reduced(curve: MontgomeryCurve<P, F>, X: F) -> Self {
+        let Z = F::one_like(&X);
+        Self { curve, X, Z }
     }
 
     #[allow(non_snake_case)]
-    pub const fn new(
-        curve: MontgomeryCurve<LIMBS, N, MOD>,
-        X: ConstMontyForm<MOD, LIMBS>,
-        Z: ConstMontyForm<MOD, LIMBS>,
-    ) -> Self {
+    pub const fn new(curve: MontgomeryCurve<P, F>, X: F, Z: F) -> Self {
         Self { curve, X, Z }
     }
 
-    const fn infinity(curve: MontgomeryCurve<LIMBS, N, MOD>) -> Self {
-        Self {
-            curve,
-            X: ConstMontyForm::ONE,
-            Z: ConstMontyForm::ZERO,
-        }
+    fn infinity(curve: MontgomeryCurve<P, F>) -> Self {
+        let x = F::one_like(curve.c24());
+        let z = F::zero_like(curve.c24());
+        Self::new(curve, x, z)
     }
 
     pub fn is_infinity(&self) -> bool {
-        self.Z == ConstMontyForm::ZERO
+        self.ct_is_infinity().into()
+    }
+
+    pub fn ct_is_infinity(&self) -> Choice {
+        self.Z.ct_eq(&F::zero_like(&self.Z))
     }
 
     #[allow(non_snake_case)]
-    pub const fn X(&self) -> ConstMontyForm<MOD, LIMBS> {
-        self.X
+    pub const fn X(&self) -> &F {
+        &self.X
     }
 
     #[allow(non_snake_case)]
-    pub const fn Z(&self) -> ConstMontyForm<MOD, LIMBS> {
-        self.Z
-    }
-
-    fn differential_add(&self, other: Self, self_minus_other: Self) -> Self {
-        let x1 = self.X;
-        let z1 = self.Z;
-        let x2 = other.X;
-        let z2 = other.Z;
-        let x3 = self_minus_other.X;
-        let z3 = self_minus_other.Z;
-
-        let a = x2 + z2;
-        let b = x2 - z2;
-        let c = x1 + z1;
-        let d = x1 - z1;
+    pub const fn Z(&self) -> &F {
+        &self.Z
+    }
+
+    /// Returns the coordinates (X, Z) of the point.
+    pub fn into_coordinates(self) -> [F; 2] {
+        [self.X, self.Z]
+    }
+
+    pub fn differential_add(&self, other: &Self, self_minus_other: &Self) -> Self {
+        let x1 = &self.X;
+        let z1 = &self.Z;
+        let x2 = &other.X;
+        let z2 = &other.Z;
+        let x3 = &self_minus_other.X;
+        let z3 = &self_minus_other.Z;
+
+        let a = x2.clone() + z2;
+        let b = x2.clone() - z2;
+        let c = x1.clone() + z1;
+        let d = x1.clone() - z1;
         let da = d * a;
         let cb = c * b;
-        let x5 = z3 * (da + cb).square();
-        let z5 = x3 * (da - cb).square();
+        let x5 = z3.clone() * (da.clone() + &cb).square();
+        let z5 = x3.clone() * (da - cb).square();
 
         Self {
-            curve: self.curve,
+            curve: self.curve.clone(),
             X: x5,
             Z: z5,
         }
     }
 
-    fn double(&self) -> Self {
-        let x1 = self.X;
-        let z1 = self.Z;
+    pub fn double(&self) -> Self {
+        let x1 = &self.X;
+        let z1 = &self.Z;
 
-        let a24 = self.curve.a24();
+        let a24_plus = self.curve.a24_plus();
+        let c24 = self.curve.c24();
 
-        let a = x1 + z1;
+        let a = x1.clone() + z1;
         let aa = a.square();
-        let b = x1 - z1;
+        let b = x1.clone() - z1;
         let bb = b.square();
-        let c = aa - bb;
-        let x3 = aa * bb;
-        let z3 = c * (bb + a24 * c);
+        let c = aa.clone() - &bb;
+        let c24_bb = c24.clone() * bb;
+        let z3 = c.clone() * (c24_bb.clone() + a24_plus.clone() * c);
+        let x3 = aa * c24_bb;
 
         Self {
-            curve: self.curve,
+            curve: self.curve.clone(),
             X: x3,
             Z: z3,
         }
     }
 
     // Returns (self + other, 2*self)
-    fn differential_add_and_double(&self, other: Self, self_minus_other: Self) -> (Self, Self) {
-        let x1 = self.X;
-        let z1 = self.Z;
-        let x2 = other.X;
-        let z2 = other.Z;
-        let x3 = self_minus_other.X;
-        let z3 = self_minus_other.Z;
-
-        let a = x1 + z1;
-        let b = x1 - z1;
-        let c = x2 + z2;
-        let d = x2 - z2;
-        let bc = b * c;
-        let ad = a * d;
-        let x5 = z3 * (bc + ad).square();
-        let z5 = x3 * (bc - ad).square();
+    fn differential_add_and_double(&self, other: &Self, self_minus_other: &Self) -> (Self, Self) {
+        let x1 = &self.X;
+        let z1 = &self.Z;
+        let x2 = &other.X;
+        let z2 = &other.Z;
+        let x3 = &self_minus_other.X;
+        let z3 = &self_minus_other.Z;
+
+        let a = x1.clone() + z1;
+        let b = x1.clone() - z1;
+        let c = x2.clone() + z2;
+        let d = x2.clone() - z2;
+        let bc = b.clone() * c;
+        let ad = a.clone() * d;
+        let x5 = z3.clone() * (bc.clone() + &ad).square();
+        let z5 = x3.clone() * (bc - ad).square();
 
         let self_plus_other = Self {
-            curve: self.curve,
+            curve: self.curve.clone(),
             X: x5,
             Z: z5,
         };
 
-        let a24 = self.curve.a24();
+        let a24_plus = self.curve.a24_plus();
+        let c24 = self.curve.c24();
 
         let aa = a.square();
         let bb = b.square();
-        let c = aa - bb;
-        let x3 = aa * bb;
-        let z3 = c * (bb + a24 * c);
+        let c = aa.clone() - &bb;
+        let c24_bb = c24.clone() * bb;
+        let z3 = c.clone() * (c24_bb.clone() + a24_plus.clone() * c);
+        let x3 = aa * c24_bb;
 
         let double_self = Self {
-            curve: self.curve,
+            curve: self.curve.clone(),
             X: x3,
             Z: z3,
         };
@@ -200,135 +195,361 @@ impl<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>>
         (self_plus_other, double_self)
     }
 
-    pub const fn multiples(self, d: Uint<LIMBS>) -> PointMultiples<LIMBS, N, MOD> {
+    pub fn multiples(self, d: u64) -> PointMultiples<P, F> {
         PointMultiples::new(self, d)
     }
-}
 
-impl<
-    const SAT_LIMBS: usize,
-    const N: usize,
-    MOD: ConstMontyParams<SAT_LIMBS>,
-    const UNSAT_LIMBS: usize,
-> MontgomeryPoint<SAT_LIMBS, N, MOD>
-where
-    Odd<Uint<SAT_LIMBS>>: PrecomputeInverter<
-            Inverter = SafeGcdInverter<SAT_LIMBS, UNSAT_LIMBS>,
-            Output = Uint<SAT_LIMBS>,
-        >,
-{
-    pub fn x(&self) -> ConstMontyForm<MOD, SAT_LIMBS> {
-        self.X * self.Z.inv().unwrap()
+    /// Constant-time Montgomery ladder.
+    ///
+    /// The ladder always runs over every bit of the precision of `scalar`, starting from infinity,
+    /// and uses conditional swaps instead of branches, so that neither the scalar nor its length
+    /// leak through timing.
+    fn ladder(&self, scalar: &P::Integer) -> Self {
+        let mut x0 = Self::infinity(self.curve.clone());
+        let mut x1 = self.clone();
+        let mut swap = Choice::from(0);
+
+        for index in (0..scalar.bits_precision()).rev() {
+            let bit = scalar.bit(index);
+            Self::conditional_swap(&mut x0, &mut x1, swap ^ bit);
+            swap = bit;
+            (x1, x0) = x0.differential_add_and_double(&x1, self);
+        }
+        Self::conditional_swap(&mut x0, &mut x1, swap);
+
+        // The differential additions are degenerate when the difference is infinity or the
+        // point (0, 0) of order 2, whose multiples are known.
+        let infinity = Self::infinity(self.curve.clone());
+        let two_torsion_multiple = Self::conditional_select(&infinity, self, scalar.bit(0));
+        let zero = F::zero_like(&self.X);
+        x0 = Self::conditional_select(&x0, &two_torsion_multiple, self.X.ct_eq(&zero));
+        Self::conditional_select(&x0, &infinity, self.ct_is_infinity())
     }
-}
 
-impl<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>> PartialEq
-    for MontgomeryPoint<LIMBS, N, MOD>
-{
-    fn eq(&self, other: &Self) -> bool {
-        let zero = ConstMontyForm::ZERO;
-        if self.Z == zero && other.Z == zero {
-            true
-        } else if self.Z == zero || other.Z == zero {
-            false
-        } else {
-            self.X * other.Z == self.Z * other.X
+    /// Multiplies the point by a public scalar.
+    ///
+    /// Unlike the constant-time `Mul` implementation, the number of ladder steps depends on the
+    /// bit length of `scalar`, so this must only be used when the scalar is not secret.
+    pub fn mul_vartime(&self, scalar: &impl Integer) -> Self {
+        let bits = scalar.bits_vartime();
+        if bits == 0 {
+            return Self::infinity(self.curve.clone());
         }
+
+        let mut x0 = self.clone();
+        let mut x1 = self.double();
+
+        for index in (0..bits - 1).rev() {
+            if scalar.bit_vartime(index) {
+                (x0, x1) = x1.differential_add_and_double(&x0, self);
+            } else {
+                (x1, x0) = x0.differential_add_and_double(&x1, self);
+            }
+        }
+
+        x0
     }
-}
 
-impl<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>> Mul<Uint<LIMBS>>
-    for MontgomeryPoint<LIMBS, N, MOD>
-{
-    type Output = Self;
+    /// Multiplies the point by a public scalar like [`MontgomeryPoint::mul_vartime`], on a curve
+    /// whose C24 is 1, as constructed by [`MontgomeryCurve::new`].
+    ///
+    /// The ladder steps skip the multiplications by C24, and by the Z-coordinate of the point if
+    /// it is 1, saving up to 2 of their 12 multiplications and squarings.
+    pub fn mul_vartime_affine(&self, scalar: &impl Integer) -> Self {
+        let one = F::one_like(&self.X);
+        debug_assert!(bool::from(self.curve.c24().ct_eq(&one)));
+        let bits = scalar.bits_vartime();
+        if bits == 0 {
+            return Self::infinity(self.curve.clone());
+        }
 
-    fn mul(self, other: Uint<LIMBS>) -> Self {
-        let mut x0 = self;
+        let z = (!bool::from(self.Z.ct_eq(&one))).then_some(&self.Z);
+        let mut x0 = self.clone();
         let mut x1 = self.double();
 
-        for index in (0..other.bits() - 1).rev() {
-            let bit = other.bit(index);
-            if bit == ConstChoice::FALSE {
-                (x1, x0) = x0.differential_add_and_double(x1, self);
+        for index in (0..bits - 1).rev() {
+            if scalar.bit_vartime(index) {
+                (x0, x1) = x1.affine_add_and_double(&x0, &self.X, z);
             } else {
-                (x0, x1) = x1.differential_add_and_double(x0, self);
+                (x1, x0) = x0.affine_add_and_double(&x1, &self.X, z);
             }
         }
 
         x0
     }
+
+    /// Returns (self + other, 2*self) like [`MontgomeryPoint::differential_add_and_double`], on a
+    /// curve whose C24 is 1, with the difference (`x` : `z`), where `None` stands for a Z of 1.
+    fn affine_add_and_double(&self, other: &Self, x: &F, z: Option<&F>) -> (Self, Self) {
+        let a = self.X.clone() + &self.Z;
+        let b = self.X.clone() - &self.Z;
+        let c = other.X.clone() + &other.Z;
+        let d = other.X.clone() - &other.Z;
+        let bc = b.clone() * c;
+        let ad = a.clone() * d;
+        let sum = (bc.clone() + &ad).square();
+        let x5 = match z {
+            Some(z) => z.clone() * sum,
+            None => sum,
+        };
+        let z5 = x.clone() * (bc - ad).square();
+
+        let aa = a.square();
+        let bb = b.square();
+        let c = aa.clone() - &bb;
+        let z3 = c.clone() * (bb.clone() + self.curve.a24_plus().clone() * c);
+        let x3 = aa * bb;
+
+        let self_plus_other = Self {
+            curve: self.curve.clone(),
+            X: x5,
+            Z: z5,
+        };
+        let double_self = Self {
+            curve: self.curve.clone(),
+            X: x3,
+            Z: z3,
+        };
+        (self_plus_other, double_self)
+    }
+
+    /// Reduces the points to Z = 1 with a single inversion, using Montgomery's trick. None of the
+    /// points may be infinity.
+    pub fn reduce_all<const LEN: usize>(points: &mut [Option<Self>; LEN]) {
+        // Each point is paired with the product of the Z-coordinates of the points before it.
+        let mut products: [Option<F>; LEN] = core::array::from_fn(|_| None);
+        let mut product: Option<F> = None;
+        for (point, previous) in points.iter().zip(&mut products) {
+            if let Some(point) = point {
+                let next = match &product {
+                    Some(product) => product.clone() * &point.Z,
+                    None => point.Z.clone(),
+                };
+                *previous = product.replace(next);
+            }
+        }
+
+        let (Some(product), Some(point)) = (product, points.iter().flatten().next()) else {
+            return;
+        };
+        let mut inverse = field::inv(&point.curve.params(), &product);
+        for (point, previous) in points.iter_mut().zip(products).rev() {
+            if let Some(point) = point {
+                let z_inverse = match previous {
+                    Some(previous) => inverse.clone() * previous,
+                    None => inverse.clone(),
+                };
+                inverse *= &point.Z;
+                point.X *= z_inverse;
+                point.Z = F::one_like(&point.Z);
+            }
+        }
+    }
+
+    /// Selects the coordinates of `a` or `b`, in constant time. Both points must be on the same
+    /// curve.
+    pub fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self {
+            curve: a.curve.clone(),
+            X: F::ct_select(&a.X, &b.X, choice),
+            Z: F::ct_select(&a.Z, &b.Z, choice),
+        }
+    }
+
+    /// Swaps the coordinates of `a` and `b`, in constant time. Both points must be on the same
+    /// curve.
+    pub fn conditional_swap(a: &mut Self, b: &mut Self, choice: Choice) {
+        F::ct_swap(&mut a.X, &mut b.X, choice);
+        F::ct_swap(&mut a.Z, &mut b.Z, choice);
+    }
+}
+
+impl<P: Params, F: Field> ConstantTimeEq for MontgomeryPoint<P, F> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        // Cross-multiplying also handles infinity: (X1 : 0) and (X2 : Z2) are only equal if Z2 is
+        // zero as well, X1 and X2 being nonzero.
+        (self.X.clone() * &other.Z).ct_eq(&(self.Z.clone() * &other.X))
+    }
+}
+
+impl<P: Params, F: Field> PartialEq for MontgomeryPoint<P, F> {
+    fn eq(&self, other: &Self) -> bool {
+        ConstantTimeEq::ct_eq(self, other).into()
+    }
+}
+
+impl<P: Params, F: Field> Mul<&P::Integer> for MontgomeryPoint<P, F> {
+    type Output = Self;
+
+    fn mul(self, other: &P::Integer) -> Self {
+        self.ladder(other)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crypto_bigint::{
+        Uint,
+        modular::{ConstMontyForm, ConstMontyParams},
+    };
+
     use crate::{CsidhParams, csidh_params::csidh_512};
 
     use super::*;
 
-    const CURVE: MontgomeryCurve<{ csidh_512::LIMBS }, { csidh_512::N }, csidh_512::MOD> =
-        MontgomeryCurve::new(CsidhParams::CSIDH_512, ConstMontyForm::ZERO);
+    type Params512 = CsidhParams<{ csidh_512::LIMBS }, { csidh_512::N }, csidh_512::MOD>;
+
+    type Point = MontgomeryPoint<Params512>;
 
-    const POINT: MontgomeryPoint<{ csidh_512::LIMBS }, { csidh_512::N }, csidh_512::MOD> =
+    fn curve() -> MontgomeryCurve<Params512> {
+        MontgomeryCurve::new(CsidhParams::CSIDH_512, ConstMontyForm::ZERO)
+    }
+
+    fn point() -> Point {
         MontgomeryPoint::new_reduced(
-            CURVE,
+            curve(),
             ConstMontyForm::new(&Uint::from_be_hex(
                 "635ea6487c006e601469a7c3030538397a1a038bf3a45d02b60ac813ffbc5b62\
                 08082059de864765636def621e70a71addf24e43ef931aaf2791ee3c89c6155a",
             )),
-        );
+        )
+    }
 
-    const POINT_TIMES_2: MontgomeryPoint<{ csidh_512::LIMBS }, { csidh_512::N }, csidh_512::MOD> =
+    fn point_times_2() -> Point {
         MontgomeryPoint::new_reduced(
-            CURVE,
+            curve(),
             ConstMontyForm::new(&Uint::from_be_hex(
                 "06943b90d5222a3d53eb510f4c2a87101b2413f8fd22f8cad1bd3a44be42d06a\
                 5c528bef417d9a41cc81b6feb56cb69ef9bc50163a2e36cabf2684430aa79f6f",
             )),
-        );
+        )
+    }
 
-    const POINT_TIMES_3: MontgomeryPoint<{ csidh_512::LIMBS }, { csidh_512::N }, csidh_512::MOD> =
+    fn point_times_3() -> Point {
         MontgomeryPoint::new_reduced(
-            CURVE,
+            curve(),
             ConstMontyForm::new(&Uint::from_be_hex(
                 "56227716288d568bcdf9022b3cbd0f5c3beea951cfe95a82050fa9fc8d9d9941\
                 9765dcd54a0feaa21527a13d69f5d19d7d7d9b32fcf4032a3d632736d0c1a6cd",
             )),
-        );
+        )
+    }
 
-    const POINT_TIMES_4: MontgomeryPoint<{ csidh_512::LIMBS }, { csidh_512::N }, csidh_512::MOD> =
+    fn point_times_4() -> Point {
         MontgomeryPoint::new_reduced(
-            CURVE,
+            curve(),
             ConstMontyForm::new(&Uint::from_be_hex(
                 "0ac1132970689f6ec5e1b5c1103fe67813355ae1ccdcc75d5c44a50f76287e62\
                 086eef0fef12a1f905be1fa226dab017d22000dd2d3e7bac1f8a54876cb55f75",
             )),
-        );
+        )
+    }
 
-    const POINT_TIMES_237: MontgomeryPoint<{ csidh_512::LIMBS }, { csidh_512::N }, csidh_512::MOD> =
+    fn point_times_237() -> Point {
         MontgomeryPoint::new_reduced(
-            CURVE,
+            curve(),
             ConstMontyForm::new(&Uint::from_be_hex(
                 "3af8740999e0b6f0d7f39593b514c9529fc4e5d393ac8907f34f9f34d646a228\
                 d9b02f85d7308bf7253058c3d957a8f99eaf97c08763b29ed48df6e918c1f1f3",
             )),
-        );
+        )
+    }
 
     #[test]
     fn multiples() {
-        let multiples = [POINT, POINT_TIMES_2, POINT_TIMES_3, POINT_TIMES_4];
-        for (i, p) in POINT.multiples(Uint::from(4u32)).enumerate() {
+        let multiples = [point(), point_times_2(), point_times_3(), point_times_4()];
+        for (i, p) in point().multiples(4).enumerate() {
             assert_eq!(p, multiples[i]);
         }
+        assert_eq!(point_times_237(), point().multiples(237).last().unwrap());
+    }
+
+    #[test]
+    fn multiplication() {
+        assert!(point() * &Uint::from(2u32) == point_times_2());
+        assert!(point() * &Uint::from(4u32) == point_times_4());
+        assert!(point() * &Uint::from(237u32) == point_times_237());
+    }
+
+    #[test]
+    fn multiplication_vartime() {
+        assert!(point().mul_vartime(&Uint::<1>::from(2u32)) == point_times_2());
+        assert!(point().mul_vartime(&Uint::<1>::from(4u32)) == point_times_4());
+        assert!(point().mul_vartime(&Uint::<1>::from(237u32)) == point_times_237());
+        assert!(point().mul_vartime(&Uint::<1>::ZERO).is_infinity());
+    }
+
+    #[test]
+    fn multiplication_affine() {
+        let five = ConstMontyForm::new(&Uint::from(5u32));
+        let scaled = MontgomeryPoint::new(curve(), *point().X() * five, five);
+        for point in [point(), scaled] {
+            assert!(point.mul_vartime_affine(&Uint::<1>::from(4u32)) == point_times_4());
+            assert!(point.mul_vartime_affine(&Uint::<1>::from(237u32)) == point_times_237());
+            assert!(point.mul_vartime_affine(&Uint::<1>::ZERO).is_infinity());
+        }
+    }
+
+    #[test]
+    fn reduction() {
+        let scaled = |point: Point, z: u32| {
+            let z = ConstMontyForm::new(&Uint::from(z));
+            MontgomeryPoint::new(curve(), *point.X() * z, z)
+        };
+        let expected = [
+            Some(point()),
+            None,
+            Some(point_times_2()),
+            Some(point_times_237()),
+        ];
+        let mut points = [
+            Some(scaled(point(), 5)),
+            None,
+            Some(point_times_2()),
+            Some(scaled(point_times_237(), 7)),
+        ];
+        MontgomeryPoint::reduce_all(&mut points);
+        let coordinates =
+            |point: &Option<Point>| point.as_ref().map(|point| (*point.X(), *point.Z()));
         assert_eq!(
-            POINT_TIMES_237,
-            POINT.multiples(Uint::from(237u32)).last().unwrap()
+            points.each_ref().map(coordinates),
+            expected.each_ref().map(coordinates)
         );
     }
 
     #[test]
-    fn multiplication() {
-        assert!(POINT * Uint::from(2u32) == POINT_TIMES_2);
-        assert!(POINT * Uint::from(4u32) == POINT_TIMES_4);
-        assert!(POINT * Uint::from(237u32) == POINT_TIMES_237);
+    fn multiplication_full_length_scalar() {
+        // The curve is supersingular, so its order p + 1 is a multiple of the order of the point,
+        // and [p]P = -P has the same x-coordinate as P.
+        let p = csidh_512::MOD::MODULUS.get();
+        assert!((point() * &p.wrapping_add(&Uint::ONE)).is_infinity());
+        assert!(point() * &p == point());
+    }
+
+    #[test]
+    fn multiplication_edge_cases() {
+        let infinity = MontgomeryPoint::infinity(curve());
+        assert!((point() * &Uint::ZERO).is_infinity());
+        assert!(point() * &Uint::ONE == point());
+        assert!((infinity.clone() * &Uint::from(237u32)).is_infinity());
+
+        let two_torsion = MontgomeryPoint::new_reduced(curve(), ConstMontyForm::ZERO);
+        assert!(two_torsion.clone() * &Uint::from(237u32) == two_torsion);
+        assert!((two_torsion * &Uint::from(4u32)).is_infinity());
+    }
+
+    #[test]
+    fn equality() {
+        let infinity = MontgomeryPoint::infinity(curve());
+        let scaled = MontgomeryPoint::new(
+            curve(),
+            *point().X() * ConstMontyForm::new(&Uint::from(5u32)),
+            ConstMontyForm::new(&Uint::from(5u32)),
+        );
+        assert!(bool::from(point().ct_eq(&scaled)));
+        assert!(!bool::from(point().ct_eq(&point_times_2())));
+        assert!(!bool::from(point().ct_eq(&infinity)));
+        assert!(!bool::from(infinity.ct_eq(&point())));
+        assert!(bool::from(infinity.ct_eq(&infinity)));
     }
 }
@@ -1,12 +1,25 @@
-use crypto_bigint::modular::ConstMontyParams;
+mod encoding;
 
-use crate::csidh_params::{CsidhParams, csidh_512, csidh_1024, csidh_1792};
+use core::{convert::Infallible, fmt};
+
+use crypto_bigint::{modular::ConstMontyParams, rand_core::CryptoRngCore};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::{
+    Error,
+    csidh_params::{CsidhParams, EvaluationMode, csidh_512, csidh_1024, csidh_1792},
+};
+
+pub use encoding::PrivateKeyFormat;
 
 /// A private key for the CSIDH key exchange.
-#[derive(Clone, Copy, Debug)]
+///
+/// The key is wiped from memory when dropped, and its [`Debug`](fmt::Debug) output does not
+/// reveal it.
+#[derive(Clone)]
 pub struct PrivateKey<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>> {
     params: CsidhParams<LIMBS, N, MOD>,
-    key: [u32; N],
+    key: [i32; N],
 }
 
 impl<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>> PrivateKey<LIMBS, N, MOD> {
@@ -14,26 +27,204 @@ impl<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>> PrivateKe
     ///
     /// # Panics
     ///
-    /// Panics if a key element is greater than 10.
+    /// Panics if a key element is not between minus and plus its bound in `params`, or if it is
+    /// odd while `params` use [`EvaluationMode::DummyFree`], which doubles the bounds. See
+    /// [`PrivateKey::try_new`] for a fallible version.
+    #[must_use]
+    pub const fn new(params: CsidhParams<LIMBS, N, MOD>, key: [i32; N]) -> Self {
+        match check(params, &key) {
+            Ok(()) => Self { params, key },
+            Err(Error::ExponentParity) => {
+                panic!("A key element must be even in dummy-free mode")
+            }
+            Err(_) => panic!("A key element must be between minus and plus its bound"),
+        }
+    }
+
+    /// Constructs a new `PrivateKey` from the given `key`, if it is valid for `params`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ExponentOutOfBounds`] if a key element is not between minus and plus its
+    /// bound in `params`, doubled in [`EvaluationMode::DummyFree`], and [`Error::ExponentParity`]
+    /// if it is odd in this mode.
+    pub const fn try_new(params: CsidhParams<LIMBS, N, MOD>, key: [i32; N]) -> Result<Self, Error> {
+        match check(params, &key) {
+            Ok(()) => Ok(Self { params, key }),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Generates a random `PrivateKey`.
+    ///
+    /// Each key element is sampled uniformly and independently between minus and plus its bound,
+    /// or among the even numbers between minus and plus twice its bound if `params` use
+    /// [`EvaluationMode::DummyFree`].
+    #[must_use]
+    pub fn random(params: CsidhParams<LIMBS, N, MOD>, rng: &mut impl CryptoRngCore) -> Self {
+        let Ok(private_key) = Self::sample(params, || Ok::<_, Infallible>(rng.next_u32()));
+        private_key
+    }
+
+    /// Generates a random `PrivateKey` like [`PrivateKey::random`], reporting the failures of
+    /// `rng`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::RngFailure`] if `rng` fails.
+    pub fn try_random(
+        params: CsidhParams<LIMBS, N, MOD>,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<Self, Error> {
+        Self::sample(params, || {
+            let mut bytes = [0; 4];
+            rng.try_fill_bytes(&mut bytes)
+                .map_err(|_| Error::RngFailure)?;
+            Ok(u32::from_le_bytes(bytes))
+        })
+    }
+
+    /// Samples a `PrivateKey` from the random integers returned by `next`.
+    fn sample<E>(
+        params: CsidhParams<LIMBS, N, MOD>,
+        mut next: impl FnMut() -> Result<u32, E>,
+    ) -> Result<Self, E> {
+        let mut key = [0; N];
+        for (element, bound) in key.iter_mut().zip(params.bounds()) {
+            *element = match params.evaluation_mode() {
+                EvaluationMode::DummyFree => {
+                    (2 * uniform_below(2 * bound + 1, &mut next)?).wrapping_sub(2 * bound) as i32
+                }
+                EvaluationMode::VariableTime | EvaluationMode::ConstantTime => {
+                    uniform_below(2 * bound + 1, &mut next)?.wrapping_sub(bound) as i32
+                }
+            };
+        }
+        Ok(Self { params, key })
+    }
+
+    /// Returns the length of a private key encoded in the given `format` with `params`.
+    #[must_use]
+    pub fn encoded_len(params: CsidhParams<LIMBS, N, MOD>, format: PrivateKeyFormat) -> usize {
+        encoding::encoded_len(&params.bounds(), format)
+    }
+
+    /// Encodes the private key in the given `format` into `out`, returning the number of bytes
+    /// written.
+    ///
+    /// The encoding embeds a tag identifying the parameters of the key, so that it can only be
+    /// decoded with the same parameters. Its length is given by [`PrivateKey::encoded_len`].
+    ///
+    /// Returns `None` if `out` is too short, or if the format cannot represent the bounds of the
+    /// parameters.
     #[must_use]
-    pub const fn new(params: CsidhParams<LIMBS, N, MOD>, key: [u32; N]) -> Self {
-        let mut i = 0;
-        while i < N {
-            assert!(key[i] <= 10, "A key element must be smaller than 10");
-            i += 1;
+    pub fn to_bytes(&self, format: PrivateKeyFormat, out: &mut [u8]) -> Option<usize> {
+        // Dummy-free keys are even, and are encoded halved within the bounds of the other modes.
+        let mut key = Zeroizing::new(self.key);
+        if matches!(self.params.evaluation_mode(), EvaluationMode::DummyFree) {
+            for element in key.iter_mut() {
+                *element /= 2;
+            }
         }
-        Self { params, key }
+        encoding::encode(&key, &self.params.bounds(), self.params.tag(), format, out)
+    }
+
+    /// Decodes a private key encoded with [`PrivateKey::to_bytes`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ParameterMismatch`] if the key was encoded with other parameters,
+    /// [`Error::InvalidLength`] if `bytes` does not have exactly the expected length, and the
+    /// errors of [`PrivateKey::try_new`] if a key element is out of range for `params`.
+    pub fn from_bytes(params: CsidhParams<LIMBS, N, MOD>, bytes: &[u8]) -> Result<Self, Error> {
+        let mut key = Zeroizing::new(encoding::decode(bytes, &params.bounds(), params.tag())?);
+        if matches!(params.evaluation_mode(), EvaluationMode::DummyFree) {
+            for element in key.iter_mut() {
+                *element *= 2;
+            }
+        }
+        Self::try_new(params, *key)
     }
 
     pub(crate) const fn params(&self) -> CsidhParams<LIMBS, N, MOD> {
         self.params
     }
 
-    pub(crate) const fn key(&self) -> [u32; N] {
-        self.key
+    pub(crate) const fn key(&self) -> &[i32; N] {
+        &self.key
+    }
+}
+
+impl<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>> fmt::Debug
+    for PrivateKey<LIMBS, N, MOD>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrivateKey")
+            .field("params", &self.params)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>> Zeroize
+    for PrivateKey<LIMBS, N, MOD>
+{
+    fn zeroize(&mut self) {
+        self.key.zeroize();
+    }
+}
+
+impl<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>> Drop
+    for PrivateKey<LIMBS, N, MOD>
+{
+    fn drop(&mut self) {
+        self.zeroize();
     }
 }
 
+impl<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>> ZeroizeOnDrop
+    for PrivateKey<LIMBS, N, MOD>
+{
+}
+
+/// Samples an integer uniformly between 0 (included) and `bound` (excluded), from the random
+/// integers returned by `next`.
+///
+/// Rejection sampling is used instead of a plain modular reduction to avoid biasing the result
+/// towards small values.
+pub(crate) fn uniform_below<E>(
+    bound: u32,
+    next: &mut impl FnMut() -> Result<u32, E>,
+) -> Result<u32, E> {
+    // Largest multiple of `bound` that fits in a u32, so that every residue is equally likely.
+    let limit = u32::MAX - u32::MAX % bound;
+    loop {
+        let sample = next()?;
+        if sample < limit {
+            return Ok(sample % bound);
+        }
+    }
+}
+
+/// Checks that `key` is a valid private key for `params`.
+const fn check<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>>(
+    params: CsidhParams<LIMBS, N, MOD>,
+    key: &[i32; N],
+) -> Result<(), Error> {
+    let dummy_free = matches!(params.evaluation_mode(), EvaluationMode::DummyFree);
+    let bounds = params.key_bounds();
+    let mut i = 0;
+    while i < N {
+        if key[i].unsigned_abs() > bounds[i] {
+            return Err(Error::ExponentOutOfBounds);
+        }
+        if dummy_free && key[i] % 2 != 0 {
+            return Err(Error::ExponentParity);
+        }
+        i += 1;
+    }
+    Ok(())
+}
+
 /// A helper type for const CSIDH-512 private key declaration.
 pub type PrivateKeyCsidh512 = PrivateKey<{ csidh_512::LIMBS }, { csidh_512::N }, csidh_512::MOD>;
 
@@ -44,3 +235,319 @@ pub type PrivateKeyCsidh1024 =
 /// A helper type for const CSIDH-1792 private key declaration.
 pub type PrivateKeyCsidh1792 =
     PrivateKey<{ csidh_1792::LIMBS }, { csidh_1792::N }, csidh_1792::MOD>;
+
+#[cfg(test)]
+mod tests {
+    use crypto_bigint::rand_core::{self, CryptoRng, RngCore};
+
+    use crate::csidh_params::toy;
+
+    use super::*;
+
+    #[test]
+    fn random_key_is_in_bounds() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10 {
+            let private_key = PrivateKey::random(CsidhParams::CSIDH_512, &mut rng);
+            assert!(private_key.key().iter().all(|&e| (-5..=5).contains(&e)));
+        }
+    }
+
+    #[test]
+    fn random_key_covers_every_exponent() {
+        let mut rng = rand::thread_rng();
+        let mut counts = [0u32; 11];
+        for _ in 0..100 {
+            let private_key = PrivateKey::random(CsidhParams::CSIDH_1792, &mut rng);
+            for &e in private_key.key() {
+                counts[(e + 5) as usize] += 1;
+            }
+        }
+        // 20100 samples, about 1827 expected per value.
+        assert!(counts.iter().all(|&count| (1500..2200).contains(&count)));
+    }
+
+    #[test]
+    fn encoding_roundtrip() {
+        let mut rng = rand::thread_rng();
+        let mut buffer = [0; 64];
+        for format in [PrivateKeyFormat::Nibbles, PrivateKeyFormat::Packed] {
+            for _ in 0..10 {
+                let private_key = PrivateKey::random(CsidhParams::CSIDH_512, &mut rng);
+                let len = private_key.to_bytes(format, &mut buffer).unwrap();
+                assert_eq!(len, PrivateKey::encoded_len(CsidhParams::CSIDH_512, format));
+                let decoded = PrivateKey::from_bytes(CsidhParams::CSIDH_512, &buffer[..len]);
+                assert_eq!(decoded.unwrap().key(), private_key.key());
+            }
+        }
+    }
+
+    #[test]
+    fn encoded_len() {
+        let params = CsidhParams::CSIDH_512;
+        assert_eq!(
+            PrivateKey::encoded_len(params, PrivateKeyFormat::Nibbles),
+            5 + 37
+        );
+        // 11^74 is just below 2^256.
+        assert_eq!(
+            PrivateKey::encoded_len(params, PrivateKeyFormat::Packed),
+            5 + 32
+        );
+        let params = CsidhParams::CSIDH_1792;
+        assert_eq!(
+            PrivateKey::encoded_len(params, PrivateKeyFormat::Nibbles),
+            5 + 101
+        );
+        assert_eq!(
+            PrivateKey::encoded_len(params, PrivateKeyFormat::Packed),
+            5 + 87
+        );
+    }
+
+    #[test]
+    fn custom_bounds() {
+        let mut rng = rand::thread_rng();
+        let bounds = [9, 0, 4, 3, 1, 2, 1, 1, 0, 1, 2];
+        let params = toy::TOY.with_bounds(bounds);
+        for _ in 0..10 {
+            let private_key = PrivateKey::random(params, &mut rng);
+            assert!(
+                private_key
+                    .key()
+                    .iter()
+                    .zip(bounds)
+                    .all(|(e, bound)| e.unsigned_abs() <= bound)
+            );
+
+            let mut buffer = [0; 16];
+            let len = private_key
+                .to_bytes(PrivateKeyFormat::Packed, &mut buffer)
+                .unwrap();
+            // log2(19 * 9 * 7 * 3^4 * 5^2) is about 21.2.
+            assert_eq!(len, 5 + 3);
+            let decoded = PrivateKey::from_bytes(params, &buffer[..len]).unwrap();
+            assert_eq!(decoded.key(), private_key.key());
+            assert_eq!(
+                PrivateKey::from_bytes(toy::TOY, &buffer[..len]).err(),
+                Some(Error::ParameterMismatch)
+            );
+
+            // A bound of 9 does not fit in a nibble.
+            assert!(
+                private_key
+                    .to_bytes(PrivateKeyFormat::Nibbles, &mut buffer)
+                    .is_none()
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_rejects_elements_above_their_bound() {
+        let params = toy::TOY.with_bounds([9, 0, 4, 3, 1, 2, 1, 1, 0, 1, 2]);
+        let _ = PrivateKey::new(params, [0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn encoding_extreme_keys() {
+        let mut buffer = [0; 16];
+        for key in [[-5; 11], [5; 11]] {
+            let private_key = PrivateKey::new(toy::TOY, key);
+            for format in [PrivateKeyFormat::Nibbles, PrivateKeyFormat::Packed] {
+                let len = private_key.to_bytes(format, &mut buffer).unwrap();
+                let decoded = PrivateKey::from_bytes(toy::TOY, &buffer[..len]).unwrap();
+                assert_eq!(decoded.key(), &key);
+            }
+        }
+    }
+
+    #[test]
+    fn to_bytes_rejects_short_buffer() {
+        let private_key = PrivateKey::new(toy::TOY, [3; 11]);
+        let len = PrivateKey::encoded_len(toy::TOY, PrivateKeyFormat::Packed);
+        let mut buffer = [0; 16];
+        assert!(
+            private_key
+                .to_bytes(PrivateKeyFormat::Packed, &mut buffer[..len - 1])
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn from_bytes_rejects_invalid_encodings() {
+        let private_key = PrivateKey::new(toy::TOY, [3; 11]);
+        for format in [PrivateKeyFormat::Nibbles, PrivateKeyFormat::Packed] {
+            let mut buffer = [0; 16];
+            let len = private_key.to_bytes(format, &mut buffer).unwrap();
+
+            let decode = |bytes| PrivateKey::from_bytes(toy::TOY, bytes).err();
+            // Trailing garbage.
+            assert_eq!(decode(&buffer[..len + 1]), Some(Error::InvalidLength));
+            // Truncated.
+            assert_eq!(decode(&buffer[..len - 1]), Some(Error::InvalidLength));
+            assert_eq!(decode(&[]), Some(Error::InvalidLength));
+            // Unknown format.
+            let mut invalid = buffer;
+            invalid[0] = 2;
+            assert_eq!(decode(&invalid[..len]), Some(Error::NonCanonicalEncoding));
+            // Parameters mismatch.
+            let mut invalid = buffer;
+            invalid[1] ^= 1;
+            assert_eq!(decode(&invalid[..len]), Some(Error::ParameterMismatch));
+            // Out-of-range digits, or an integer above the product of the radices.
+            let mut invalid = buffer;
+            invalid[5..len].fill(0xff);
+            let expected = match format {
+                PrivateKeyFormat::Nibbles => Error::ExponentOutOfBounds,
+                PrivateKeyFormat::Packed => Error::NonCanonicalEncoding,
+            };
+            assert_eq!(decode(&invalid[..len]), Some(expected));
+        }
+
+        // The unused high nibble of an odd-length key must be zero.
+        let mut buffer = [0; 16];
+        let len = private_key
+            .to_bytes(PrivateKeyFormat::Nibbles, &mut buffer)
+            .unwrap();
+        buffer[len - 1] |= 0x10;
+        assert_eq!(
+            PrivateKey::from_bytes(toy::TOY, &buffer[..len]).err(),
+            Some(Error::NonCanonicalEncoding)
+        );
+    }
+
+    #[test]
+    fn from_bytes_rejects_other_params() {
+        let private_key = PrivateKey::new(CsidhParams::CSIDH_512, [0; 74]);
+        let mut buffer = [0; 64];
+        let len = private_key
+            .to_bytes(PrivateKeyFormat::Nibbles, &mut buffer)
+            .unwrap();
+        assert!(PrivateKey::from_bytes(CsidhParams::CSIDH_512, &buffer[..len]).is_ok());
+        assert_eq!(
+            PrivateKey::from_bytes(CsidhParams::CSIDH_1024, &buffer[..len]).err(),
+            Some(Error::ParameterMismatch)
+        );
+    }
+
+    #[test]
+    fn dummy_free_keys_are_even() {
+        let mut rng = rand::thread_rng();
+        let params = toy::TOY.with_evaluation_mode(EvaluationMode::DummyFree);
+        let mut extremes = [false; 2];
+        for _ in 0..100 {
+            let private_key = PrivateKey::random(params, &mut rng);
+            assert!(
+                private_key
+                    .key()
+                    .iter()
+                    .all(|&e| (-10..=10).contains(&e) && e % 2 == 0)
+            );
+            extremes[0] |= private_key.key().contains(&-10);
+            extremes[1] |= private_key.key().contains(&10);
+        }
+        assert_eq!(extremes, [true; 2]);
+
+        let private_key = PrivateKey::new(params, [-10, -8, -6, -4, -2, 0, 2, 4, 6, 8, 10]);
+        let mut buffer = [0; 16];
+        let len = private_key
+            .to_bytes(PrivateKeyFormat::Nibbles, &mut buffer)
+            .unwrap();
+        assert_eq!(
+            len,
+            PrivateKey::encoded_len(toy::TOY, PrivateKeyFormat::Nibbles)
+        );
+        assert_eq!(
+            PrivateKey::from_bytes(params, &buffer[..len])
+                .unwrap()
+                .key(),
+            private_key.key()
+        );
+        assert_eq!(
+            PrivateKey::from_bytes(toy::TOY, &buffer[..len]).err(),
+            Some(Error::ParameterMismatch)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn dummy_free_rejects_odd_keys() {
+        let params = toy::TOY.with_evaluation_mode(EvaluationMode::DummyFree);
+        let _ = PrivateKey::new(params, [-3; 11]);
+    }
+
+    #[test]
+    fn try_new_reports_invalid_elements() {
+        let params = toy::TOY.with_bounds([9, 0, 4, 3, 1, 2, 1, 1, 0, 1, 2]);
+        assert!(PrivateKey::try_new(params, [-9, 0, 4, 3, 1, 2, 1, 1, 0, 1, 2]).is_ok());
+        assert_eq!(
+            PrivateKey::try_new(params, [0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]).err(),
+            Some(Error::ExponentOutOfBounds)
+        );
+        let dummy_free = toy::TOY.with_evaluation_mode(EvaluationMode::DummyFree);
+        assert_eq!(
+            PrivateKey::try_new(dummy_free, [-3; 11]).err(),
+            Some(Error::ExponentParity)
+        );
+        assert_eq!(
+            PrivateKey::try_new(dummy_free, [12; 11]).err(),
+            Some(Error::ExponentOutOfBounds)
+        );
+        assert!(PrivateKey::try_new(dummy_free, [-10; 11]).is_ok());
+    }
+
+    #[test]
+    fn try_random_reports_rng_failures() {
+        struct FailingRng;
+
+        impl RngCore for FailingRng {
+            fn next_u32(&mut self) -> u32 {
+                unreachable!("only try_fill_bytes is called")
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                unreachable!("only try_fill_bytes is called")
+            }
+
+            fn fill_bytes(&mut self, _: &mut [u8]) {
+                unreachable!("only try_fill_bytes is called")
+            }
+
+            fn try_fill_bytes(&mut self, _: &mut [u8]) -> Result<(), rand_core::Error> {
+                Err(rand_core::Error::from(
+                    core::num::NonZeroU32::new(rand_core::Error::CUSTOM_START).unwrap(),
+                ))
+            }
+        }
+
+        impl CryptoRng for FailingRng {}
+
+        let mut rng = rand::thread_rng();
+        let private_key = PrivateKey::try_random(toy::TOY, &mut rng).unwrap();
+        assert!(private_key.key().iter().all(|&e| (-5..=5).contains(&e)));
+        assert_eq!(
+            PrivateKey::try_random(toy::TOY, &mut FailingRng).err(),
+            Some(Error::RngFailure)
+        );
+    }
+
+    #[test]
+    fn debug_is_redacted() {
+        extern crate std;
+        use std::format;
+
+        let private_key = PrivateKey::new(toy::TOY, [-4; 11]);
+        let debug = format!("{private_key:?}");
+        assert!(debug.starts_with("PrivateKey { params: "));
+        assert!(debug.ends_with(", .. }"));
+        assert!(!debug.contains("key: "));
+    }
+
+    #[test]
+    fn zeroize_wipes_key() {
+        let mut private_key = PrivateKey::new(toy::TOY, [-4; 11]);
+        private_key.zeroize();
+        assert_eq!(private_key.key(), &[0; 11]);
+    }
+}
@@ -0,0 +1,213 @@
+//! Byte encodings of private keys.
+//!
+//! An encoded private key is made of a header followed by a payload:
+//!
+//! - 1 byte identifying the [`PrivateKeyFormat`],
+//! - 4 bytes holding the little-endian tag of the parameters the key belongs to,
+//! - the key elements, packed according to the format.
+//!
+//! A key element bounded by b ranges from -b to b, and is stored as the digit `element + b` in
+//! radix 2b + 1.
+
+use crate::Error;
+
+const HEADER_LEN: usize = 5;
+
+/// Formats available to encode a [`PrivateKey`](crate::PrivateKey).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrivateKeyFormat {
+    /// Each key element, plus its bound, is stored in a 4-bit nibble, two elements per byte,
+    /// lowest nibble first. Only available if every bound is at most 7.
+    Nibbles,
+    /// The key elements, plus their bound, are the digits of a single mixed-radix integer,
+    /// stored in little-endian order. This is within a byte of the information-theoretic bound of
+    /// log2((2b<sub>1</sub> + 1) ⋯ (2b<sub>N</sub> + 1)) bits.
+    Packed,
+}
+
+impl PrivateKeyFormat {
+    const fn id(self) -> u8 {
+        match self {
+            Self::Nibbles => 0,
+            Self::Packed => 1,
+        }
+    }
+
+    const fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Self::Nibbles),
+            1 => Some(Self::Packed),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the length of an encoded key with the given `bounds`, header included.
+pub(crate) fn encoded_len<const N: usize>(bounds: &[u32; N], format: PrivateKeyFormat) -> usize {
+    HEADER_LEN + payload_len(bounds, format)
+}
+
+fn payload_len<const N: usize>(bounds: &[u32; N], format: PrivateKeyFormat) -> usize {
+    match format {
+        PrivateKeyFormat::Nibbles => N.div_ceil(2),
+        PrivateKeyFormat::Packed => {
+            // The product of the radices fits in N limbs as each radix is below 2^64.
+            let mut max = [0u64; N];
+            if N > 0 {
+                max[0] = 1;
+            }
+            for &bound in bounds {
+                mul_add(&mut max, radix(bound), 0);
+            }
+            // The largest encoded value is the product of the radices, minus 1.
+            sub_one(&mut max);
+            bits(&max).div_ceil(8)
+        }
+    }
+}
+
+fn radix(bound: u32) -> u64 {
+    2 * u64::from(bound) + 1
+}
+
+/// Returns the digit storing `element`.
+fn digit(element: i32, bound: u32) -> u64 {
+    (i64::from(element) + i64::from(bound)) as u64
+}
+
+/// Writes the encoding of `key` to `out`, returning the number of bytes written.
+///
+/// Returns `None` if `out` is too short, or if a bound does not fit in a nibble with the
+/// [`PrivateKeyFormat::Nibbles`] format.
+pub(crate) fn encode<const N: usize>(
+    key: &[i32; N],
+    bounds: &[u32; N],
+    tag: u32,
+    format: PrivateKeyFormat,
+    out: &mut [u8],
+) -> Option<usize> {
+    if format == PrivateKeyFormat::Nibbles && bounds.iter().any(|&bound| radix(bound) > 16) {
+        return None;
+    }
+    let len = encoded_len(bounds, format);
+    let out = out.get_mut(..len)?;
+    out[0] = format.id();
+    out[1..HEADER_LEN].copy_from_slice(&tag.to_le_bytes());
+    let payload = &mut out[HEADER_LEN..];
+
+    match format {
+        PrivateKeyFormat::Nibbles => {
+            payload.fill(0);
+            for (i, (&element, &bound)) in key.iter().zip(bounds).enumerate() {
+                payload[i / 2] |= (digit(element, bound) as u8) << (4 * (i % 2));
+            }
+        }
+        PrivateKeyFormat::Packed => {
+            let mut value = [0u64; N];
+            for (&element, &bound) in key.iter().zip(bounds).rev() {
+                mul_add(&mut value, radix(bound), digit(element, bound));
+            }
+            for (i, byte) in payload.iter_mut().enumerate() {
+                *byte = (value[i / 8] >> (8 * (i % 8))) as u8;
+            }
+        }
+    }
+
+    Some(len)
+}
+
+/// Decodes a key encoded with [`encode`].
+///
+/// Fails if the format is unknown or cannot represent `bounds`, if the tag does not match, if the
+/// length is not exactly the expected one or if a key element is out of range.
+pub(crate) fn decode<const N: usize>(
+    bytes: &[u8],
+    bounds: &[u32; N],
+    tag: u32,
+) -> Result<[i32; N], Error> {
+    let id = *bytes.first().ok_or(Error::InvalidLength)?;
+    let format = PrivateKeyFormat::from_id(id).ok_or(Error::NonCanonicalEncoding)?;
+    if format == PrivateKeyFormat::Nibbles && bounds.iter().any(|&bound| radix(bound) > 16) {
+        return Err(Error::ParameterMismatch);
+    }
+    // The tag is checked first, so that a key of other parameters is reported as such even if
+    // its length differs.
+    if bytes.get(1..HEADER_LEN).ok_or(Error::InvalidLength)? != tag.to_le_bytes() {
+        return Err(Error::ParameterMismatch);
+    }
+    if bytes.len() != encoded_len(bounds, format) {
+        return Err(Error::InvalidLength);
+    }
+    let payload = &bytes[HEADER_LEN..];
+
+    let mut key = [0; N];
+    match format {
+        PrivateKeyFormat::Nibbles => {
+            for (i, (element, &bound)) in key.iter_mut().zip(bounds).enumerate() {
+                let digit = (payload[i / 2] >> (4 * (i % 2))) & 0xf;
+                if u64::from(digit) >= radix(bound) {
+                    return Err(Error::ExponentOutOfBounds);
+                }
+                *element = (i64::from(digit) - i64::from(bound)) as i32;
+            }
+            // The unused high nibble of an odd-length key must be zero.
+            if N % 2 == 1 && payload[N / 2] >> 4 != 0 {
+                return Err(Error::NonCanonicalEncoding);
+            }
+        }
+        PrivateKeyFormat::Packed => {
+            let mut value = [0u64; N];
+            for (i, &byte) in payload.iter().enumerate() {
+                value[i / 8] |= u64::from(byte) << (8 * (i % 8));
+            }
+            for (element, &bound) in key.iter_mut().zip(bounds) {
+                *element = (div_rem(&mut value, radix(bound)) as i64 - i64::from(bound)) as i32;
+            }
+            // Anything left means the encoded integer was at least the product of the radices.
+            if value.iter().any(|&limb| limb != 0) {
+                return Err(Error::NonCanonicalEncoding);
+            }
+        }
+    }
+
+    Ok(key)
+}
+
+/// Computes `value * m + a` in place, `value` being a little-endian integer.
+fn mul_add(value: &mut [u64], m: u64, a: u64) {
+    let mut carry = a;
+    for limb in value.iter_mut() {
+        let wide = u128::from(*limb) * u128::from(m) + u128::from(carry);
+        *limb = wide as u64;
+        carry = (wide >> 64) as u64;
+    }
+    debug_assert_eq!(carry, 0, "overflow in mixed-radix arithmetic");
+}
+
+/// Divides `value` by `d` in place and returns the remainder.
+fn div_rem(value: &mut [u64], d: u64) -> u64 {
+    let mut remainder = 0;
+    for limb in value.iter_mut().rev() {
+        let wide = (u128::from(remainder) << 64) | u128::from(*limb);
+        *limb = (wide / u128::from(d)) as u64;
+        remainder = (wide % u128::from(d)) as u64;
+    }
+    remainder
+}
+
+fn sub_one(value: &mut [u64]) {
+    for limb in value.iter_mut() {
+        let (result, borrow) = limb.overflowing_sub(1);
+        *limb = result;
+        if !borrow {
+            return;
+        }
+    }
+}
+
+fn bits(value: &[u64]) -> usize {
+    value
+        .iter()
+        .rposition(|&limb| limb != 0)
+        .map_or(0, |i| 64 * i + (64 - value[i].leading_zeros() as usize))
+}
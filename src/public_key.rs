@@ -1,11 +1,14 @@
 use crypto_bigint::{
-    Odd, PrecomputeInverter, Uint,
-    modular::{ConstMontyForm, ConstMontyParams, SafeGcdInverter},
+    Encoding, Uint,
+    modular::{ConstMontyForm, ConstMontyParams},
     rand_core::CryptoRngCore,
 };
 
 use crate::{
-    csidh::csidh, csidh_params::CsidhParams, montgomery_curve::MontgomeryCurve,
+    Error,
+    csidh::csidh,
+    csidh_params::CsidhParams,
+    montgomery_curve::{BATCH_SIZE, MontgomeryCurve, ProductTree},
     private_key::PrivateKey,
 };
 
@@ -15,18 +18,11 @@ pub struct PublicKey<const LIMBS: usize, MOD: ConstMontyParams<LIMBS>> {
     key: ConstMontyForm<MOD, LIMBS>,
 }
 
-impl<const SAT_LIMBS: usize, MOD: ConstMontyParams<SAT_LIMBS>, const UNSAT_LIMBS: usize>
-    PublicKey<SAT_LIMBS, MOD>
-where
-    Odd<Uint<SAT_LIMBS>>: PrecomputeInverter<
-            Inverter = SafeGcdInverter<SAT_LIMBS, UNSAT_LIMBS>,
-            Output = Uint<SAT_LIMBS>,
-        >,
-{
+impl<const LIMBS: usize, MOD: ConstMontyParams<LIMBS>> PublicKey<LIMBS, MOD> {
     /// Computes the public key associated with the given private key.
     #[must_use]
     pub fn from<const N: usize>(
-        private_key: PrivateKey<SAT_LIMBS, N, MOD>,
+        private_key: &PrivateKey<LIMBS, N, MOD>,
         rng: &mut impl CryptoRngCore,
     ) -> Self {
         Self {
@@ -40,21 +36,441 @@ where
     }
 
     /// Constructs a `PublicKey` from the foreign public key, if the key is valid.
+    ///
+    /// The key must be smaller than p and describe a supersingular curve. See
+    /// [`PublicKey::try_new`] for the reason of a rejection.
     #[must_use]
     pub fn new<const N: usize>(
-        params: CsidhParams<SAT_LIMBS, N, MOD>,
-        key: Uint<SAT_LIMBS>,
+        params: CsidhParams<LIMBS, N, MOD>,
+        key: Uint<LIMBS>,
         rng: &mut impl CryptoRngCore,
     ) -> Option<Self> {
-        let key = ConstMontyForm::new(&key);
+        Self::validate(params, key, rng).ok()
+    }
+
+    /// Constructs a `PublicKey` from the foreign public key, if the key is valid.
+    ///
+    /// The key is validated as described in [`PublicKey::try_new_with_certificate`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first check the key fails.
+    pub fn try_new<const N: usize>(
+        params: CsidhParams<LIMBS, N, MOD>,
+        key: Uint<LIMBS>,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<Self, Error> {
+        Self::validate(params, key, rng)
+    }
+
+    /// Constructs a `PublicKey` from the foreign public key, if the key is valid, along with a
+    /// certificate of its supersingularity.
+    ///
+    /// The key is the Montgomery coefficient A of a curve. Following the CSIDH paper, it is
+    /// checked in this order that:
+    ///
+    /// 1. A is smaller than p, so that each key has a single encoding,
+    /// 2. A is not 2 or -2, whose curves are singular,
+    /// 3. A is not 0, if the parameters do not allow the starting curve,
+    /// 4. the curve is supersingular, which is proven by a point P such that the order of \[4\]P
+    ///    has a divisor d greater than 4√p, made of primes of the parameters.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NonCanonicalEncoding`], [`Error::SingularCurve`],
+    /// [`Error::StartingCurve`] or [`Error::OrdinaryCurve`] for the first check the key fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csidh::{CsidhParams, PublicKey, Uint};
+    ///
+    /// let mut rng = rand::thread_rng();
+    /// let params = CsidhParams::CSIDH_512;
+    /// let (public_key, certificate) =
+    ///     PublicKey::try_new_with_certificate(params, Uint::ZERO, &mut rng).unwrap();
+    /// assert!(certificate.verify(params, &public_key));
+    /// ```
+    pub fn try_new_with_certificate<const N: usize>(
+        params: CsidhParams<LIMBS, N, MOD>,
+        key: Uint<LIMBS>,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(Self, SupersingularityCertificate<LIMBS>), Error> {
+        let key = Self::coefficient(params, key)?;
+        let (x, order) = MontgomeryCurve::new(params, key)
+            .certify(rng)
+            .ok_or(Error::OrdinaryCurve)?;
+        let certificate = SupersingularityCertificate {
+            x: x.retrieve(),
+            order,
+        };
+        Ok((Self { key }, certificate))
+    }
+
+    fn validate<const N: usize>(
+        params: CsidhParams<LIMBS, N, MOD>,
+        key: Uint<LIMBS>,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<Self, Error> {
+        let key = Self::coefficient(params, key)?;
         if MontgomeryCurve::new(params, key).is_supersingular(rng) {
-            Some(Self { key })
+            Ok(Self { key })
         } else {
-            None
+            Err(Error::OrdinaryCurve)
+        }
+    }
+
+    /// Returns the coefficient A of the curve of `key`, after the checks of
+    /// [`PublicKey::try_new_with_certificate`] that do not involve the curve itself.
+    fn coefficient<const N: usize>(
+        params: CsidhParams<LIMBS, N, MOD>,
+        key: Uint<LIMBS>,
+    ) -> Result<ConstMontyForm<MOD, LIMBS>, Error> {
+        // `ConstMontyForm::new` silently reduces its input, which would make several encodings
+        // decode to the same key.
+        if key >= MOD::MODULUS.get() {
+            return Err(Error::NonCanonicalEncoding);
+        }
+        let two = Uint::from_u8(2);
+        if key == two || key == MOD::MODULUS.get().wrapping_sub(&two) {
+            return Err(Error::SingularCurve);
+        }
+        if key == Uint::ZERO && !params.starting_curve_allowed() {
+            return Err(Error::StartingCurve);
         }
+        Ok(ConstMontyForm::new(&key))
     }
 
-    pub(crate) const fn key(&self) -> ConstMontyForm<MOD, SAT_LIMBS> {
+    /// Validates a batch of foreign public keys, returning for each key what
+    /// [`PublicKey::try_new`] would.
+    ///
+    /// Every key gets its own result, whatever the validity of the others. The product tree of the
+    /// primes, used to compute the orders of points, is built once for the whole batch, and the
+    /// curves are verified together by groups of 16 as the iterator is consumed: the random points
+    /// of a group are drawn in one pass, and their multiples share their inversions, which makes
+    /// the batch cheaper than calling [`PublicKey::try_new`] on each key.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use csidh::{CsidhParams, Error, PublicKey, Uint};
+    ///
+    /// let mut rng = rand::thread_rng();
+    /// let params = CsidhParams::CSIDH_512;
+    /// let keys = [Uint::ZERO, Uint::from(2u32), Uint::from(3u32)];
+    /// let mut results = PublicKey::validate_batch(params, &keys, &mut rng);
+    /// assert!(results.next().unwrap().is_ok());
+    /// assert_eq!(results.next(), Some(Err(Error::SingularCurve)));
+    /// assert_eq!(results.next(), Some(Err(Error::OrdinaryCurve)));
+    /// ```
+    pub fn validate_batch<const N: usize>(
+        params: CsidhParams<LIMBS, N, MOD>,
+        keys: &[Uint<LIMBS>],
+        rng: &mut impl CryptoRngCore,
+    ) -> impl Iterator<Item = Result<Self, Error>> {
+        let tree = ProductTree::new(&params);
+        keys.chunks(BATCH_SIZE).flat_map(move |keys| {
+            let coefficients: [Option<Result<_, Error>>; BATCH_SIZE] =
+                core::array::from_fn(|index| {
+                    keys.get(index).map(|&key| Self::coefficient(params, key))
+                });
+            let curves = coefficients
+                .each_ref()
+                .map(|coefficient| match coefficient {
+                    Some(Ok(key)) => Some(MontgomeryCurve::new(params, *key)),
+                    _ => None,
+                });
+            let supersingular = MontgomeryCurve::are_supersingular(&curves, &tree, rng);
+            coefficients
+                .into_iter()
+                .zip(supersingular)
+                .map_while(|(coefficient, supersingular)| {
+                    coefficient.map(|key| match key {
+                        Ok(key) if supersingular => Ok(Self { key }),
+                        Ok(_) => Err(Error::OrdinaryCurve),
+                        Err(error) => Err(error),
+                    })
+                })
+        })
+    }
+
+    pub(crate) const fn key(&self) -> ConstMontyForm<MOD, LIMBS> {
         self.key
     }
 }
+
+impl<const LIMBS: usize, MOD: ConstMontyParams<LIMBS>> PublicKey<LIMBS, MOD>
+where
+    Uint<LIMBS>: Encoding,
+{
+    /// Encodes the public key as the little-endian bytes of the Montgomery coefficient A of its
+    /// curve.
+    ///
+    /// The encoding has a fixed length for a given parameter set and is canonical: A is always
+    /// fully reduced modulo p.
+    #[must_use]
+    pub fn to_bytes(&self) -> <Uint<LIMBS> as Encoding>::Repr {
+        self.key.retrieve().to_le_bytes()
+    }
+
+    /// Decodes a foreign public key encoded with [`PublicKey::to_bytes`], if the key is valid.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidLength`] if the encoding does not have the expected length, and
+    /// [`Error::NonCanonicalEncoding`] if it encodes an integer greater than or equal to p. The
+    /// key itself is then validated the same way as in [`PublicKey::try_new`].
+    pub fn from_bytes<const N: usize>(
+        params: CsidhParams<LIMBS, N, MOD>,
+        bytes: &[u8],
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<Self, Error> {
+        let bytes =
+            <Uint<LIMBS> as Encoding>::Repr::try_from(bytes).map_err(|_| Error::InvalidLength)?;
+        Self::validate(params, Uint::from_le_bytes(bytes), rng)
+    }
+}
+
+/// Proof that the curve of a [`PublicKey`] is supersingular, returned by
+/// [`PublicKey::try_new_with_certificate`].
+///
+/// It is made of the x-coordinate of a point P of the curve, and of a divisor d of the order of
+/// \[4\]P greater than 4√p, made of primes of the parameters. Since d divides both p + 1 and the
+/// number of points of the curve, which lies between p + 1 - 2√p and p + 1 + 2√p, the curve has
+/// p + 1 points and is supersingular.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SupersingularityCertificate<const LIMBS: usize> {
+    x: Uint<LIMBS>,
+    order: Uint<LIMBS>,
+}
+
+impl<const LIMBS: usize> SupersingularityCertificate<LIMBS> {
+    /// Returns the x-coordinate of the point P.
+    #[must_use]
+    pub const fn x(&self) -> Uint<LIMBS> {
+        self.x
+    }
+
+    /// Returns the divisor d of the order of \[4\]P.
+    #[must_use]
+    pub const fn order(&self) -> Uint<LIMBS> {
+        self.order
+    }
+
+    /// Checks the certificate for `public_key`, without any randomness.
+    ///
+    /// The point P must be on the curve, and d must be the divisor of the order of \[4\]P that
+    /// validation finds.
+    #[must_use]
+    pub fn verify<const N: usize, MOD: ConstMontyParams<LIMBS>>(
+        &self,
+        params: CsidhParams<LIMBS, N, MOD>,
+        public_key: &PublicKey<LIMBS, MOD>,
+    ) -> bool {
+        self.x < MOD::MODULUS.get()
+            && self.order > params.sqrt_of_p_times_4()
+            && MontgomeryCurve::new(params, public_key.key)
+                .check_certificate(ConstMontyForm::new(&self.x), &self.order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::csidh_params::{csidh_512, toy};
+
+    use super::*;
+
+    #[test]
+    fn bytes_roundtrip() {
+        let mut rng = rand::thread_rng();
+        let private_key = PrivateKey::random(toy::TOY, &mut rng);
+        let public_key = PublicKey::from(&private_key, &mut rng);
+        let bytes = public_key.to_bytes();
+        assert_eq!(bytes.len(), 8);
+        assert_eq!(
+            PublicKey::from_bytes(toy::TOY, &bytes, &mut rng),
+            Ok(public_key)
+        );
+    }
+
+    #[test]
+    fn from_bytes_rejects_wrong_length() {
+        let mut rng = rand::thread_rng();
+        assert_eq!(
+            PublicKey::from_bytes(toy::TOY, &[0; 7], &mut rng),
+            Err(Error::InvalidLength)
+        );
+        assert_eq!(
+            PublicKey::from_bytes(toy::TOY, &[0; 9], &mut rng),
+            Err(Error::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn from_bytes_rejects_non_canonical() {
+        let mut rng = rand::thread_rng();
+        // A = 0 is a valid key, but p encodes the same residue.
+        assert!(PublicKey::from_bytes(toy::TOY, &[0; 8], &mut rng).is_ok());
+        let p = toy::MOD::MODULUS.get().to_le_bytes();
+        assert_eq!(
+            PublicKey::from_bytes(toy::TOY, &p, &mut rng),
+            Err(Error::NonCanonicalEncoding)
+        );
+        assert_eq!(
+            PublicKey::new(toy::TOY, toy::MOD::MODULUS.get(), &mut rng),
+            None
+        );
+    }
+
+    #[test]
+    fn try_new_reports_invalid_curves() {
+        let mut rng = rand::thread_rng();
+        let p = toy::MOD::MODULUS.get();
+        let two = Uint::from(2u32);
+        for key in [two, p.wrapping_sub(&two)] {
+            assert_eq!(
+                PublicKey::try_new(toy::TOY, key, &mut rng),
+                Err(Error::SingularCurve)
+            );
+        }
+        assert_eq!(
+            PublicKey::try_new(toy::TOY, Uint::from(3u32), &mut rng),
+            Err(Error::OrdinaryCurve)
+        );
+        assert_eq!(
+            PublicKey::try_new(toy::TOY, p, &mut rng),
+            Err(Error::NonCanonicalEncoding)
+        );
+    }
+
+    #[test]
+    fn validate_batch_matches_new() {
+        let mut rng = rand::thread_rng();
+        let params = toy::TOY;
+        let private_key = PrivateKey::random(params, &mut rng);
+        let public_key = PublicKey::from(&private_key, &mut rng);
+        let keys = [
+            public_key.key().retrieve(),
+            Uint::from(3u32),
+            toy::MOD::MODULUS.get(),
+            Uint::ZERO,
+            Uint::from(2u32),
+            Uint::from(6u32),
+        ];
+        let expected = keys.map(|key| PublicKey::try_new(params, key, &mut rng));
+        assert!(PublicKey::validate_batch(params, &keys, &mut rng).eq(expected));
+        assert_eq!(
+            expected.map(|result| result.is_ok()),
+            [true, false, false, true, false, true]
+        );
+    }
+
+    #[test]
+    fn validate_batch_spans_several_groups() {
+        let mut rng = rand::thread_rng();
+        let params = toy::TOY;
+        let keys: [Uint<{ toy::LIMBS }>; 40] = core::array::from_fn(|index| {
+            if index % 3 == 0 {
+                Uint::from(3u32)
+            } else {
+                let private_key = PrivateKey::random(params, &mut rng);
+                PublicKey::from(&private_key, &mut rng).key().retrieve()
+            }
+        });
+        let results = PublicKey::validate_batch(params, &keys, &mut rng);
+        for (index, result) in results.enumerate() {
+            assert_eq!(result.is_ok(), index % 3 != 0);
+        }
+        assert_eq!(
+            PublicKey::validate_batch(params, &keys, &mut rng).count(),
+            40
+        );
+        assert_eq!(PublicKey::validate_batch(params, &[], &mut rng).count(), 0);
+    }
+
+    /// Checks that each key is rejected by every validation function, with the error of the
+    /// first check it fails.
+    fn check_rejections<const LIMBS: usize, const N: usize, MOD: ConstMontyParams<LIMBS>>(
+        params: CsidhParams<LIMBS, N, MOD>,
+        vectors: &[(Uint<LIMBS>, Error)],
+    ) where
+        Uint<LIMBS>: Encoding,
+    {
+        let mut rng = rand::thread_rng();
+        for &(key, error) in vectors {
+            assert_eq!(PublicKey::validate(params, key, &mut rng), Err(error));
+            assert_eq!(
+                PublicKey::try_new_with_certificate(params, key, &mut rng),
+                Err(error)
+            );
+            let bytes = key.to_le_bytes();
+            assert_eq!(
+                PublicKey::from_bytes(params, bytes.as_ref(), &mut rng),
+                Err(error)
+            );
+            assert!(PublicKey::validate_batch(params, &[key], &mut rng).eq([Err(error)]));
+        }
+    }
+
+    #[test]
+    fn rejection_vectors() {
+        let p = toy::MOD::MODULUS.get();
+        check_rejections(
+            toy::TOY.with_starting_curve_allowed(false),
+            &[
+                (p, Error::NonCanonicalEncoding),
+                (p.wrapping_add(&Uint::ONE), Error::NonCanonicalEncoding),
+                (Uint::MAX, Error::NonCanonicalEncoding),
+                (Uint::from(2u32), Error::SingularCurve),
+                (p.wrapping_sub(&Uint::from(2u32)), Error::SingularCurve),
+                (Uint::ZERO, Error::StartingCurve),
+                (Uint::ONE, Error::OrdinaryCurve),
+                (Uint::from(3u32), Error::OrdinaryCurve),
+                (p.wrapping_sub(&Uint::from(3u32)), Error::OrdinaryCurve),
+                (p.wrapping_sub(&Uint::ONE), Error::OrdinaryCurve),
+            ],
+        );
+    }
+
+    #[test]
+    fn rejection_vectors_csidh_512() {
+        let p = csidh_512::MOD::MODULUS.get();
+        check_rejections(
+            CsidhParams::CSIDH_512.with_starting_curve_allowed(false),
+            &[
+                (p, Error::NonCanonicalEncoding),
+                (Uint::MAX, Error::NonCanonicalEncoding),
+                (Uint::from(2u32), Error::SingularCurve),
+                (p.wrapping_sub(&Uint::from(2u32)), Error::SingularCurve),
+                (Uint::ZERO, Error::StartingCurve),
+                (Uint::from(3u32), Error::OrdinaryCurve),
+                (p.wrapping_sub(&Uint::from(3u32)), Error::OrdinaryCurve),
+            ],
+        );
+    }
+
+    #[test]
+    fn certificates_verify() {
+        let mut rng = rand::thread_rng();
+        let params = toy::TOY;
+        let private_key = PrivateKey::random(params, &mut rng);
+        let key = PublicKey::from(&private_key, &mut rng);
+
+        let (public_key, certificate) =
+            PublicKey::try_new_with_certificate(params, key.key().retrieve(), &mut rng).unwrap();
+        assert_eq!(public_key, key);
+        assert!(certificate.order() > params.sqrt_of_p_times_4());
+        assert!(certificate.verify(params, &public_key));
+
+        let tampered = SupersingularityCertificate {
+            order: certificate.order().wrapping_add(&Uint::ONE),
+            ..certificate
+        };
+        assert!(!tampered.verify(params, &public_key));
+        let tampered = SupersingularityCertificate {
+            x: certificate.x().wrapping_add(&toy::MOD::MODULUS.get()),
+            ..certificate
+        };
+        assert!(!tampered.verify(params, &public_key));
+    }
+}
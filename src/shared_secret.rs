@@ -1,30 +1,30 @@
+use core::fmt;
+
 use crypto_bigint::{
-    Odd, PrecomputeInverter, Uint,
-    modular::{ConstMontyForm, ConstMontyParams, SafeGcdInverter},
+    modular::{ConstMontyForm, ConstMontyParams},
     rand_core::CryptoRngCore,
 };
+use hkdf::HkdfExtract;
+use sha2::Sha256;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use crate::{csidh::csidh, private_key::PrivateKey, public_key::PublicKey};
 
 /// A shared secret created with the CSIDH key exchange.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// The secret is wiped from memory when dropped, and its [`Debug`](fmt::Debug) output does not
+/// reveal it.
+#[derive(Clone, PartialEq, Eq)]
 pub struct SharedSecret<const LIMBS: usize, MOD: ConstMontyParams<LIMBS>> {
     shared_secret: ConstMontyForm<MOD, LIMBS>,
 }
 
-impl<const SAT_LIMBS: usize, MOD: ConstMontyParams<SAT_LIMBS>, const UNSAT_LIMBS: usize>
-    SharedSecret<SAT_LIMBS, MOD>
-where
-    Odd<Uint<SAT_LIMBS>>: PrecomputeInverter<
-            Inverter = SafeGcdInverter<SAT_LIMBS, UNSAT_LIMBS>,
-            Output = Uint<SAT_LIMBS>,
-        >,
-{
+impl<const LIMBS: usize, MOD: ConstMontyParams<LIMBS>> SharedSecret<LIMBS, MOD> {
     /// Computes a shared secret from a foreign public key and a private key.
     #[must_use]
     pub fn from<const N: usize>(
-        foreign_public_key: PublicKey<SAT_LIMBS, MOD>,
-        private_key: PrivateKey<SAT_LIMBS, N, MOD>,
+        foreign_public_key: PublicKey<LIMBS, MOD>,
+        private_key: &PrivateKey<LIMBS, N, MOD>,
         rng: &mut impl CryptoRngCore,
     ) -> Self {
         Self {
@@ -36,4 +36,171 @@ where
             ),
         }
     }
+
+    /// Derives a symmetric key from the shared secret and fills `out` with it.
+    ///
+    /// The key is derived with HKDF-SHA-256 (RFC 5869): the input keying material is the
+    /// canonical little-endian encoding of the Montgomery coefficient of the shared curve, the
+    /// salt is empty and `context` is used as the info string. Different `context` values yield
+    /// independent keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` is longer than 8160 bytes (255 times the SHA-256 output size).
+    pub fn derive_key(&self, context: &[u8], out: &mut [u8]) {
+        let mut extract = HkdfExtract::<Sha256>::new(None);
+        input_coefficient(&mut extract, self.shared_secret);
+        expand(extract, context, out);
+    }
+
+    /// Derives a symmetric key from the shared secret and both public keys of the exchange,
+    /// and fills `out` with it.
+    ///
+    /// This works like [`SharedSecret::derive_key`], except that the canonical encodings of both
+    /// public keys are appended to the input keying material, binding the key to the transcript
+    /// of the exchange. The public keys are sorted before being hashed, so both parties derive
+    /// the same key regardless of the order in which they pass them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` is longer than 8160 bytes (255 times the SHA-256 output size).
+    pub fn derive_key_with_public_keys(
+        &self,
+        public_keys: [&PublicKey<LIMBS, MOD>; 2],
+        context: &[u8],
+        out: &mut [u8],
+    ) {
+        let [first, second] = public_keys.map(|public_key| public_key.key());
+        let (low, high) = if first.retrieve() <= second.retrieve() {
+            (first, second)
+        } else {
+            (second, first)
+        };
+
+        let mut extract = HkdfExtract::<Sha256>::new(None);
+        input_coefficient(&mut extract, self.shared_secret);
+        input_coefficient(&mut extract, low);
+        input_coefficient(&mut extract, high);
+        expand(extract, context, out);
+    }
+}
+
+impl<const LIMBS: usize, MOD: ConstMontyParams<LIMBS>> fmt::Debug for SharedSecret<LIMBS, MOD> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedSecret").finish_non_exhaustive()
+    }
+}
+
+impl<const LIMBS: usize, MOD: ConstMontyParams<LIMBS>> Zeroize for SharedSecret<LIMBS, MOD> {
+    fn zeroize(&mut self) {
+        self.shared_secret.zeroize();
+    }
+}
+
+impl<const LIMBS: usize, MOD: ConstMontyParams<LIMBS>> Drop for SharedSecret<LIMBS, MOD> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<const LIMBS: usize, MOD: ConstMontyParams<LIMBS>> ZeroizeOnDrop for SharedSecret<LIMBS, MOD> {}
+
+/// Feeds the canonical little-endian encoding of `coefficient` to `extract`.
+///
+/// The encoding is the same on every platform: limbs are little-endian and stored least
+/// significant first.
+fn input_coefficient<const LIMBS: usize, MOD: ConstMontyParams<LIMBS>>(
+    extract: &mut HkdfExtract<Sha256>,
+    coefficient: ConstMontyForm<MOD, LIMBS>,
+) {
+    for word in coefficient.retrieve().as_words() {
+        extract.input_ikm(&word.to_le_bytes());
+    }
+}
+
+fn expand(extract: HkdfExtract<Sha256>, context: &[u8], out: &mut [u8]) {
+    let (_, hkdf) = extract.finalize();
+    hkdf.expand(context, out)
+        .expect("The derived key must be at most 8160 bytes long");
+}
+
+#[cfg(test)]
+mod tests {
+    use crypto_bigint::Uint;
+
+    use crate::csidh_params::toy;
+
+    use super::*;
+
+    #[test]
+    fn derived_keys_match() {
+        let mut rng = rand::thread_rng();
+        let alice_private_key = PrivateKey::random(toy::TOY, &mut rng);
+        let alice_public_key = PublicKey::from(&alice_private_key, &mut rng);
+        let bob_private_key = PrivateKey::random(toy::TOY, &mut rng);
+        let bob_public_key = PublicKey::from(&bob_private_key, &mut rng);
+
+        let alice_shared_secret = SharedSecret::from(bob_public_key, &alice_private_key, &mut rng);
+        let bob_shared_secret = SharedSecret::from(alice_public_key, &bob_private_key, &mut rng);
+
+        let mut alice_key = [0; 32];
+        let mut bob_key = [0; 32];
+        alice_shared_secret.derive_key(b"context", &mut alice_key);
+        bob_shared_secret.derive_key(b"context", &mut bob_key);
+        assert_eq!(alice_key, bob_key);
+
+        let mut alice_bound_key = [0; 32];
+        let mut bob_bound_key = [0; 32];
+        alice_shared_secret.derive_key_with_public_keys(
+            [&alice_public_key, &bob_public_key],
+            b"context",
+            &mut alice_bound_key,
+        );
+        bob_shared_secret.derive_key_with_public_keys(
+            [&bob_public_key, &alice_public_key],
+            b"context",
+            &mut bob_bound_key,
+        );
+        assert_eq!(alice_bound_key, bob_bound_key);
+        assert_ne!(alice_key, alice_bound_key);
+    }
+
+    #[test]
+    fn derive_key_depends_on_context() {
+        let shared_secret = SharedSecret::<{ toy::LIMBS }, toy::MOD> {
+            shared_secret: ConstMontyForm::new(&Uint::from_u64(0x0123_4567_89ab)),
+        };
+        let mut first = [0; 32];
+        let mut second = [0; 32];
+        shared_secret.derive_key(b"first", &mut first);
+        shared_secret.derive_key(b"second", &mut second);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn derive_key_known_answer() {
+        let shared_secret = SharedSecret::<{ toy::LIMBS }, toy::MOD> {
+            shared_secret: ConstMontyForm::new(&Uint::from_u64(0x0123_4567_89ab)),
+        };
+        let mut key = [0; 16];
+        shared_secret.derive_key(b"csidh", &mut key);
+        // HKDF-SHA-256 with an empty salt, IKM = ab 89 67 45 23 01 00 00 and info = "csidh".
+        assert_eq!(
+            key,
+            [
+                232, 220, 82, 162, 32, 183, 81, 229, 91, 243, 193, 27, 233, 229, 61, 254
+            ]
+        );
+    }
+
+    #[test]
+    fn debug_is_redacted() {
+        extern crate std;
+        use std::format;
+
+        let shared_secret = SharedSecret::<{ toy::LIMBS }, toy::MOD> {
+            shared_secret: ConstMontyForm::new(&Uint::from_u64(0x0123_4567_89ab)),
+        };
+        assert_eq!(format!("{shared_secret:?}"), "SharedSecret { .. }");
+    }
 }
@@ -0,0 +1,262 @@
+//! Strategies to compute a sequence of isogenies from a single point, as introduced for SIDH in
+//! <i><a href=https://eprint.iacr.org/2011/506>
+//! De Feo, L., Jao, D., Plût, J.: Towards quantum-resistant cryptosystems from supersingular
+//! elliptic curve isogenies. Journal of Mathematical Cryptology 8(3). pp. 209–247 (2014)</a></i>
+//! and adapted to CSIDH in <i><a href=https://eprint.iacr.org/2020/417>
+//! Chi-Domínguez, J.-J., Rodríguez-Henríquez, F.: Optimal strategies for CSIDH. Advances in
+//! Mathematics of Communications 16(2). pp. 383–411 (2022)</a></i>.
+//!
+//! Each round of the class group action starts from a point whose order is the product of the
+//! primes of the round, and computes one isogeny per prime. The kernel of the isogeny of the first
+//! prime is found by multiplying the point by all the other primes, but the intermediate multiples
+//! are worth keeping: after being pushed through the isogeny, they give the kernels of the next
+//! primes with far fewer multiplications. A strategy tells which multiples to keep.
+//!
+//! A strategy for n primes is a binary tree with n leaves. At a node, the point is multiplied by
+//! the primes of the right subtree to get a point for the left subtree, and the original point is
+//! kept aside. Once all the isogenies of the left subtree are computed, the point kept aside has
+//! been pushed through them, and it is used for the right subtree.
+
+use crypto_bigint::WrappingMul;
+
+use crate::{
+    csidh_params::Params, field::Field, montgomery_curve::MontgomeryCurve,
+    montgomery_point::MontgomeryPoint,
+};
+
+/// Shapes of the optimal strategies for every number of primes up to N.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Strategy<const N: usize> {
+    /// Number of leaves in the left subtree of the optimal strategy for n leaves, at index n - 1.
+    splits: [u16; N],
+}
+
+impl<const N: usize> Strategy<N> {
+    /// Computes the optimal strategies for the primes `lis`, with isogenies of degree at least
+    /// `sqrt_velu_threshold` computed with the square-root Vélu formulas.
+    pub const fn optimal(lis: &[u64; N], sqrt_velu_threshold: u64) -> Self {
+        let mut splits = [0; N];
+        optimal_splits(lis, sqrt_velu_threshold, &mut [0; N], &mut splits);
+        Self { splits }
+    }
+
+    /// Returns the number of leaves in the left subtree of the optimal strategy for n leaves, at
+    /// index n - 1.
+    pub const fn splits(&self) -> &[u16] {
+        &self.splits
+    }
+}
+
+/// Computes the splits of the optimal strategies for the primes `lis` into `splits`, as stored in
+/// [`Strategy`], with isogenies of degree at least `sqrt_velu_threshold` computed with the
+/// square-root Vélu formulas. `costs` and `splits` must have one element per prime.
+///
+/// The primes of a round vary, so the costs are averaged over all the primes. A strategy with
+/// k leaves on the left and n - k on the right costs the two subtrees, plus n - k
+/// multiplications by a prime and k evaluations of an isogeny.
+pub const fn optimal_splits(
+    lis: &[u64],
+    sqrt_velu_threshold: u64,
+    costs: &mut [u64],
+    splits: &mut [u16],
+) {
+    let n = lis.len();
+    assert!(n <= u16::MAX as usize, "Too many primes");
+
+    let mut multiplication = 0;
+    let mut evaluation = 0;
+    let mut i = 0;
+    while i < n {
+        multiplication += multiplication_cost(lis[i]);
+        evaluation += evaluation_cost(lis[i], sqrt_velu_threshold);
+        i += 1;
+    }
+
+    let mut leaves = 2;
+    while leaves <= n {
+        let mut best = u64::MAX;
+        let mut left = 1;
+        while left < leaves {
+            let right = leaves - left;
+            let cost = costs[left - 1]
+                + costs[right - 1]
+                + right as u64 * multiplication
+                + left as u64 * evaluation;
+            if cost < best {
+                best = cost;
+                splits[leaves - 1] = left as u16;
+            }
+            left += 1;
+        }
+        costs[leaves - 1] = best;
+        leaves += 1;
+    }
+}
+
+/// Returns the number of leaves in the left subtree of the optimal strategy for `leaves` leaves,
+/// given the `splits` of [`Strategy`].
+const fn split(splits: &[u16], leaves: usize) -> usize {
+    splits[leaves - 1] as usize
+}
+
+/// Approximate cost of a multiplication by `li`, in field multiplications: a ladder step per
+/// bit.
+const fn multiplication_cost(li: u64) -> u64 {
+    10 * (u64::BITS - li.leading_zeros()) as u64
+}
+
+/// Approximate cost of pushing a point through an isogeny of degree `li`, in field
+/// multiplications.
+const fn evaluation_cost(li: u64, sqrt_velu_threshold: u64) -> u64 {
+    if li >= sqrt_velu_threshold && li >= 5 {
+        li / 2 + 20 * li.isqrt()
+    } else {
+        5 * li
+    }
+}
+
+/// Points kept aside while walking through a strategy.
+///
+/// They all lie on the current curve of the walk, and only their coordinates are stored.
+pub struct Pending<'a, P: Params, F: Field, const M: usize> {
+    curve: MontgomeryCurve<P, F>,
+    coordinates: &'a mut [[[F; 2]; M]],
+}
+
+impl<P: Params, F: Field, const M: usize> Pending<'_, P, F, M> {
+    /// Replaces each group of points kept aside by `f` applied to it.
+    ///
+    /// `f` must return points on the curve the walk continues with.
+    pub fn update(
+        &mut self,
+        mut f: impl FnMut([MontgomeryPoint<P, F>; M]) -> [MontgomeryPoint<P, F>; M],
+    ) {
+        for coordinates in self.coordinates.iter_mut() {
+            let points = coordinates
+                .clone()
+                .map(|[x, z]| MontgomeryPoint::new(self.curve.clone(), x, z));
+            *coordinates = f(points).map(MontgomeryPoint::into_coordinates);
+        }
+    }
+}
+
+/// Walks through the optimal strategy for the primes of indices `primes`, starting from `points`
+/// on `curve`, and returns the last curve of the walk.
+///
+/// The `points` go through the same multiplications, and `leaf` is called for each prime, in
+/// order, with the current curve, the index of the prime, the multiples of the `points` whose
+/// order divides it, and the points kept aside. It must push the points kept aside out of the
+/// prime, through the isogeny it computes or by multiplying them by the prime, and return the new
+/// curve.
+///
+/// The primes are multiplied in variable time, so they must be public, or the evaluation
+/// variable-time anyway.
+pub fn walk<P: Params, F: Field, const M: usize>(
+    curve: MontgomeryCurve<P, F>,
+    primes: &[usize],
+    points: [MontgomeryPoint<P, F>; M],
+    mut leaf: impl FnMut(
+        MontgomeryCurve<P, F>,
+        usize,
+        [MontgomeryPoint<P, F>; M],
+        &mut Pending<P, F, M>,
+    ) -> MontgomeryCurve<P, F>,
+) -> MontgomeryCurve<P, F> {
+    let params = curve.params();
+    let lis = params.lis();
+    let splits = params.splits();
+
+    let zero = F::zero_like(curve.c24());
+    let mut curve = curve;
+    let mut points = points;
+    let mut stack = params.array(core::array::from_fn(|_| [zero.clone(), zero.clone()]));
+    let stack = stack.as_mut();
+    let mut ranges = params.array((0, 0));
+    let ranges = ranges.as_mut();
+    let mut len = 0;
+    let (mut start, mut end) = (0, primes.len());
+
+    while start < end {
+        while end - start > 1 {
+            let middle = start + split(splits, end - start);
+            stack[len] = points
+                .each_ref()
+                .map(|point| [point.X().clone(), point.Z().clone()]);
+            ranges[len] = (middle, end);
+            len += 1;
+
+            let mut cofactor = params.integer(1);
+            for i in &primes[middle..end] {
+                cofactor = cofactor.wrapping_mul(&params.integer(lis[*i]));
+            }
+            points = points.map(|point| point.mul_vartime(&cofactor));
+            end = middle;
+        }
+
+        let mut pending = Pending {
+            curve: curve.clone(),
+            coordinates: &mut stack[..len],
+        };
+        curve = leaf(curve, primes[start], points, &mut pending);
+
+        if len == 0 {
+            break;
+        }
+        len -= 1;
+        points = stack[len]
+            .clone()
+            .map(|[x, z]| MontgomeryPoint::new(curve.clone(), x, z));
+        (start, end) = ranges[len];
+    }
+    curve
+}
+
+#[cfg(test)]
+mod tests {
+    use crypto_bigint::{U64, Uint, modular::ConstMontyForm};
+
+    use super::*;
+    use crate::{
+        CsidhParams,
+        csidh_params::{csidh_512, toy},
+    };
+
+    #[test]
+    fn splits_are_in_range() {
+        let params = CsidhParams::CSIDH_512;
+        let splits = params.splits();
+        for leaves in 2..=csidh_512::N {
+            assert!((1..leaves).contains(&split(splits, leaves)));
+        }
+    }
+
+    #[test]
+    fn walk_visits_every_prime_with_a_kernel() {
+        let mut rng = rand::thread_rng();
+        let curve =
+            MontgomeryCurve::new(toy::TOY, ConstMontyForm::<toy::MOD, { toy::LIMBS }>::ZERO);
+        let lis = toy::TOY.lis();
+        let primes = [0, 2, 3, 5, 8, 9, 10];
+
+        // Removes the primes that are not walked through from the order of the point.
+        let mut cofactor = Uint::from(4u32);
+        for (i, li) in lis.iter().enumerate() {
+            if !primes.contains(&i) {
+                cofactor *= Uint::<{ toy::LIMBS }>::from(*li);
+            }
+        }
+        let point = curve.random_point(&mut rng) * &cofactor;
+
+        let mut visited = [0; toy::N];
+        let mut len = 0;
+        walk(curve, &primes, [point], |curve, i, [point_k], pending| {
+            let li = U64::from_u64(lis[i]);
+            assert!(point_k.mul_vartime(&li).is_infinity());
+            pending.update(|[point]| [point.mul_vartime(&li)]);
+            visited[len] = i;
+            len += 1;
+            curve
+        });
+        assert_eq!(visited[..len], primes);
+    }
+}